@@ -1,25 +1,48 @@
-use circuit::bigint::biguint::CircuitBuilderBiguint;
+use circuit::bigint::biguint::{BigUintTarget, CircuitBuilderBiguint};
 use circuit::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
 use circuit::types::config::{Builder, F};
-use num::BigUint;
-use plonky2::hash::hash_types::HashOutTarget;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget};
+use plonky2::iop::target::BoolTarget;
 use plonky2::plonk::config::AlgebraicHasher;
 
-use crate::blob_domain::get_brp_roots_of_unity_as_constant;
+use crate::blob_domain::{blob_domain, get_brp_roots_of_unity_as_constant};
 use crate::bls12_381_scalar_field::{BLS12381Scalar, BLS12_381_SCALAR_LIMBS};
+use crate::byte_hashes::{digest_to_biguint, Byte, ByteHash, DIGEST_BYTES};
 
 pub const BLOB_WIDTH: usize = 4096;
 pub const BLOB_WIDTH_BITS: usize = 12;
 
-// Represents evaluations of polynomial P at points w_0, w_1, ..., w_4096
-// where w_i is the i'th 4096'th root of unity in bls12-381 scalar field.
-pub struct BlobPolynomial([NonNativeTarget<BLS12381Scalar>; BLOB_WIDTH]);
+/// Number of wrap flags packed into one Goldilocks element by [`BlobPolynomial::commit_packed`].
+pub const PACKED_FLAGS_PER_ELEMENT: usize = 63;
 
-impl BlobPolynomial {
+// Represents evaluations of polynomial P at points w_0, w_1, ..., w_(N-1)
+// where w_i is the i'th N'th root of unity in bls12-381 scalar field.
+// N is BLOB_WIDTH for EIP-4844 blobs, other powers of two allow small test circuits, partial blobs
+// and extended data.
+pub struct BlobPolynomial<const N: usize = BLOB_WIDTH>([NonNativeTarget<BLS12381Scalar>; N]);
+
+impl<const N: usize> BlobPolynomial<N> {
+    /// Allocates the blob elements and constrains each of them to be canonical, i.e. below the
+    /// BLS12-381 scalar field modulus. EIP-4844 rejects blobs with non-canonical elements, and
+    /// without this check the blob hashed into the circuit commitment could differ from the one
+    /// the KZG commitment was computed over.
     pub fn new(builder: &mut Builder) -> Self {
+        const { assert!(N.is_power_of_two(), "blob width must be a power of two") };
+
+        let modulus_minus_one = builder.constant_biguint(&(BLS12381Scalar::order() - 1u32));
+
         BlobPolynomial(
-            (0..BLOB_WIDTH)
-                .map(|_| builder.add_virtual_nonnative_target_sized(BLS12_381_SCALAR_LIMBS))
+            (0..N)
+                .map(|_| {
+                    let coeff = builder.add_virtual_nonnative_target_sized(BLS12_381_SCALAR_LIMBS);
+
+                    // coeff <= modulus - 1, the comparison also range checks every limb to u32
+                    let is_canonical = builder.cmp_biguint(&coeff.value, &modulus_minus_one);
+                    builder.assert_one(is_canonical.target);
+
+                    coeff
+                })
                 .collect::<Vec<_>>()
                 .try_into()
                 .unwrap(),
@@ -40,71 +63,172 @@ impl BlobPolynomial {
         )
     }
 
+    /// Allocates the circuit commitment as a public input and constrains it to equal
+    /// [`Self::commit`]. Proofs of other circuits committing to the same blob data can then be
+    /// linked to this one through that public input, without recomputing the hash downstream.
+    ///
+    /// The returned target is the public input, to be passed on to the Fiat-Shamir derivation.
+    pub fn commit_public<H: AlgebraicHasher<F>>(&self, builder: &mut Builder) -> HashOutTarget {
+        let external_commitment = builder.add_virtual_hash();
+        builder.register_public_inputs(&external_commitment.elements);
+
+        let circuit_commitment = self.commit::<H>(builder);
+        builder.connect_hashes(circuit_commitment, external_commitment);
+
+        external_commitment
+    }
+
+    /// Same as [`Self::commit`], but absorbs two u32 limbs per Goldilocks element, roughly halving
+    /// the number of hash inputs and permutations: a 4096 element blob is 16645 inputs instead of
+    /// 32768, i.e. 2081 instead of 4096 permutations at a rate of 8. The packing itself adds a
+    /// `mul_const_add` and an `is_equal` per limb pair, which in a plonky2 circuit outweighs the
+    /// saved permutations, one gate each: 7255 gates against 4096 for [`Self::commit`], see
+    /// `examples/commitment_cost.rs`. Fewer permutations only pay off where hashing dominates,
+    /// e.g. when the commitment is recomputed natively.
+    ///
+    /// A limb pair packs to ``lo + hi * 2^32``, which is only reduced by the Goldilocks order
+    /// when ``hi = 2^32 - 1``. To keep the packing injective each pair also yields a wrap flag
+    /// ``hi == 2^32 - 1``; the flags are packed 63 per element and absorbed after the pairs. This
+    /// relies on every limb being range checked to u32, which [`Self::new`] ensures.
+    pub fn commit_packed<H: AlgebraicHasher<F>>(&self, builder: &mut Builder) -> HashOutTarget {
+        let two_to_the_32 = F::from_canonical_u64(1 << 32);
+        let u32_max = builder.constant(F::from_canonical_u32(u32::MAX));
+
+        let mut packed_limbs = Vec::with_capacity(N * BLS12_381_SCALAR_LIMBS / 2);
+        let mut wrap_flags = Vec::with_capacity(N * BLS12_381_SCALAR_LIMBS / 2);
+        for coeff in self.0.iter() {
+            for pair in coeff.value.limbs.chunks(2) {
+                let (low, high) = (pair[0].0, pair[1].0);
+                packed_limbs.push(builder.mul_const_add(two_to_the_32, high, low));
+                wrap_flags.push(builder.is_equal(high, u32_max));
+            }
+        }
+
+        let packed_wrap_flags = wrap_flags
+            .chunks(PACKED_FLAGS_PER_ELEMENT)
+            .map(|flags| builder.le_sum(flags.iter()))
+            .collect::<Vec<_>>();
+
+        builder.hash_n_to_hash_no_pad::<H>(
+            packed_limbs
+                .into_iter()
+                .chain(packed_wrap_flags)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The canonical EIP-4844 encoding of the blob: every element as 32 big-endian bytes.
+    pub fn to_bytes_be(&self, builder: &mut Builder) -> Vec<Byte> {
+        self.0
+            .iter()
+            .flat_map(|coeff| coeff.value.limbs.iter().rev())
+            .flat_map(|limb| {
+                let bits = builder.split_le(limb.0, 32);
+                (0..4)
+                    .rev()
+                    .map(move |byte| bits[8 * byte..8 * (byte + 1)].try_into().unwrap())
+            })
+            .collect()
+    }
+
+    /// Commits to the blob by hashing [`Self::to_bytes_be`] with a byte-oriented hash, so the
+    /// commitment can be recomputed on-chain from the raw blob. The digest is returned as a
+    /// big-endian 256-bit integer, i.e. the ``uint256`` an L1 contract would see.
+    ///
+    /// This is far more expensive than [`Self::commit`]: both hashes are evaluated bit by bit,
+    /// over 128 KiB of input.
+    pub fn commit_bytes(&self, builder: &mut Builder, hash: ByteHash) -> BigUintTarget {
+        let message = self.to_bytes_be(builder);
+        let digest = hash.hash_circuit(builder, &message);
+        digest_to_biguint(builder, &digest)
+    }
+
+    /// Byte-hash counterpart of [`Self::commit_public`], exposing the digest of
+    /// [`Self::commit_bytes`] as 8 public u32 limbs.
+    ///
+    /// [`PoeCircuit`](crate::poe_circuit::PoeCircuit) has no option for it: at 16 elements the
+    /// byte commitment already takes about 54000 gates with Keccak-256 and 29000 with SHA-256,
+    /// which for a 4096 element blob grows to millions of gates, several times the rest of the
+    /// circuit. It is meant for circuits over smaller blobs, or as a building block until the
+    /// hash is delegated to a dedicated circuit.
+    pub fn commit_bytes_public(&self, builder: &mut Builder, hash: ByteHash) -> BigUintTarget {
+        let external_commitment = builder.add_virtual_biguint_target(DIGEST_BYTES / 4);
+        builder.register_public_input_biguint(&external_commitment);
+
+        let circuit_commitment = self.commit_bytes(builder, hash);
+        builder.connect_biguint(&circuit_commitment, &external_commitment);
+
+        external_commitment
+    }
+
     /// Evaluate a polynomial (in evaluation form) at an arbitrary point ``z``.
     /// - When ``z`` is in the domain, the evaluation can be found by indexing the polynomial at the
-    /// position that ``z`` is in the domain.
+    ///   position that ``z`` is in the domain.
     /// - When ``z`` is not in the domain, the barycentric formula is used:
     ///    f(z) = (z**WIDTH - 1) / WIDTH  *  sum_(i=0)^WIDTH  (f(DOMAIN[i]) * DOMAIN[i]) / (z - DOMAIN[i])
     ///
     /// In our case:
     /// - ``z`` is the challenge point in Fp
-    /// - ``WIDTH`` is BLOB_WIDTH
+    /// - ``WIDTH`` is N, i.e. BLOB_WIDTH for blobs
     /// - ``DOMAIN`` is the bit_reversal_permutation roots of unity
     /// - ``f(DOMAIN[i])`` is the blob[i]
     ///
+    /// Each denominator ``z - DOMAIN[i]`` is inverted on its own: a hinted non-native inversion
+    /// costs about as much as a multiplication, so Montgomery's batch inversion, at three
+    /// multiplications per element, needs more gates (`examples/eval_at_gate_count.rs`). The
+    /// division by ``WIDTH`` is a multiplication by
+    /// [`BlobDomain::size_inv`](crate::blob_domain::BlobDomain::size_inv). All of this only depends
+    /// on ``z``, see [`BarycentricWeights`].
     pub fn eval_at(
         &self,
         builder: &mut Builder,
         x: &NonNativeTarget<BLS12381Scalar>,
     ) -> NonNativeTarget<BLS12381Scalar> {
-        let zero_big = builder.zero_biguint();
-        let one_big = builder.one_biguint();
-        let one_nonnative = builder.biguint_to_nonnative(&one_big);
+        let weights = BarycentricWeights::<N>::new(builder, x);
+        self.eval_with_weights(builder, &weights)
+    }
 
-        let roots_of_unity_brp = get_brp_roots_of_unity_as_constant(builder);
+    /// Evaluates the blob at several points, e.g. independent challenges for soundness
+    /// amplification, using [`BarycentricWeights::new_many`].
+    pub fn eval_at_many(
+        &self,
+        builder: &mut Builder,
+        xs: &[NonNativeTarget<BLS12381Scalar>],
+    ) -> Vec<NonNativeTarget<BLS12381Scalar>> {
+        BarycentricWeights::<N>::new_many(builder, xs)
+            .iter()
+            .map(|weights| self.eval_with_weights(builder, weights))
+            .collect()
+    }
+
+    /// Same as [`Self::eval_at`], with the point-dependent part of the barycentric formula
+    /// computed by the caller. Evaluating several blobs at one point this way costs a
+    /// multiplication per blob element, the inversions are shared.
+    pub fn eval_with_weights(
+        &self,
+        builder: &mut Builder,
+        weights: &BarycentricWeights<N>,
+    ) -> NonNativeTarget<BLS12381Scalar> {
+        let zero_big = builder.zero_biguint();
 
         let mut result = builder.zero_nonnative();
-        let mut cp_is_not_root_of_unity = builder._true();
         let mut barycentric_evaluation = builder.zero_nonnative();
-        for i in 0..BLOB_WIDTH {
-            let nominator_i = builder.mul_nonnative(&roots_of_unity_brp[i], &self.0[i]);
-
-            // avoid division by zero
-            // safe_denominator_i = denominator_i       (denominator_i != 0)
-            // safe_denominator_i = 1                   (denominator_i == 0)
-            let denominator_i = builder.sub_nonnative(x, &roots_of_unity_brp[i]);
-            let is_zero_denominator_i = builder.is_zero_biguint(&denominator_i.value);
-            let safe_denominator_i =
-                builder.select_biguint(is_zero_denominator_i, &one_big, &denominator_i.value);
-            let safe_denominator_i = builder.biguint_to_nonnative(&safe_denominator_i);
-
-            // update `cp_is_not_root_of_unity`
-            // cp_is_not_root_of_unity = 1          (initialize)
-            // cp_is_not_root_of_unity = 0          (denominator_i == 0)
-            let non_zero_denominator_i = builder.not(is_zero_denominator_i);
-            cp_is_not_root_of_unity = builder.and(cp_is_not_root_of_unity, non_zero_denominator_i);
+        for i in 0..N {
+            let term_i = builder.mul_nonnative(&self.0[i], &weights.weights[i]);
+            barycentric_evaluation = builder.add_nonnative(&barycentric_evaluation, &term_i);
 
             // update `result`
             // result = blob[i]     (challenge_point = roots_of_unity_brp[i])
             let select_blob_i_big =
-                builder.select_biguint(is_zero_denominator_i, &self.0[i].value, &zero_big);
+                builder.select_biguint(weights.is_domain_point[i], &self.0[i].value, &zero_big);
             let select_blob_i = builder.biguint_to_nonnative(&select_blob_i_big);
             result = builder.add_nonnative(&result, &select_blob_i);
-
-            let term_i = BLS12381Scalar::divide(builder, &nominator_i, &safe_denominator_i);
-            barycentric_evaluation = builder.add_nonnative(&barycentric_evaluation, &term_i);
         }
-
-        let cp_to_the_width = BLS12381Scalar::pow_to_const(builder, &x, BLOB_WIDTH);
-        let cp_to_the_width_minus_one = builder.sub_nonnative(&cp_to_the_width, &one_nonnative);
-        let width_big = builder.constant_biguint(&BigUint::from(BLOB_WIDTH));
-        let width = builder.biguint_to_nonnative(&width_big);
-        let factor = BLS12381Scalar::divide(builder, &cp_to_the_width_minus_one, &width);
-        barycentric_evaluation = builder.mul_nonnative(&barycentric_evaluation, &factor);
+        barycentric_evaluation = builder.mul_nonnative(&barycentric_evaluation, &weights.factor);
 
         // if challenge_point is a root of unity, then result = blob[i], else result = barycentric_evaluation
         let select_evaluation_big = builder.select_biguint(
-            cp_is_not_root_of_unity,
+            weights.cp_is_not_root_of_unity,
             &barycentric_evaluation.value,
             &zero_big,
         );
@@ -113,3 +237,406 @@ impl BlobPolynomial {
         builder.add_nonnative(&result, &select_evaluation)
     }
 }
+
+/// The part of the barycentric formula in [`BlobPolynomial::eval_at`] that depends only on the
+/// evaluation point ``z``:
+/// - the weights ``DOMAIN[i] / (z - DOMAIN[i])``
+/// - the factor ``(z**WIDTH - 1) / WIDTH``
+/// - which domain element, if any, ``z`` is equal to
+///
+/// Computing it once lets any number of blobs be evaluated at ``z`` with
+/// [`BlobPolynomial::eval_with_weights`].
+pub struct BarycentricWeights<const N: usize = BLOB_WIDTH> {
+    weights: Vec<NonNativeTarget<BLS12381Scalar>>,
+    factor: NonNativeTarget<BLS12381Scalar>,
+    is_domain_point: Vec<BoolTarget>,
+    cp_is_not_root_of_unity: BoolTarget,
+}
+
+impl<const N: usize> BarycentricWeights<N> {
+    pub fn new(builder: &mut Builder, x: &NonNativeTarget<BLS12381Scalar>) -> Self {
+        Self::new_many(builder, std::slice::from_ref(x))
+            .pop()
+            .unwrap()
+    }
+
+    /// Weights for several evaluation points. The domain constants are allocated once and shared
+    /// by all points, each point then costs one non-native inversion per domain element and one
+    /// exponentiation for ``x^WIDTH``.
+    pub fn new_many(builder: &mut Builder, xs: &[NonNativeTarget<BLS12381Scalar>]) -> Vec<Self> {
+        const { assert!(N.is_power_of_two(), "blob width must be a power of two") };
+
+        let one_big = builder.one_biguint();
+        let one_nonnative = BLS12381Scalar::constant(builder, BLS12381Scalar::ONE);
+
+        let domain = blob_domain(N);
+        let roots_of_unity_brp = get_brp_roots_of_unity_as_constant::<N>(builder);
+
+        xs.iter()
+            .map(|x| {
+                let mut cp_is_not_root_of_unity = builder._true();
+                let mut is_domain_point = Vec::with_capacity(N);
+                let mut weights = Vec::with_capacity(N);
+                for root_i in roots_of_unity_brp.iter() {
+                    // avoid division by zero
+                    // safe_denominator_i = denominator_i       (denominator_i != 0)
+                    // safe_denominator_i = 1                   (denominator_i == 0)
+                    let denominator_i = builder.sub_nonnative(x, root_i);
+                    let is_zero_denominator_i = builder.is_zero_biguint(&denominator_i.value);
+                    let safe_denominator_i = builder.select_biguint(
+                        is_zero_denominator_i,
+                        &one_big,
+                        &denominator_i.value,
+                    );
+                    let safe_denominator_i = builder.biguint_to_nonnative(&safe_denominator_i);
+                    is_domain_point.push(is_zero_denominator_i);
+
+                    // update `cp_is_not_root_of_unity`
+                    // cp_is_not_root_of_unity = 1          (initialize)
+                    // cp_is_not_root_of_unity = 0          (denominator_i == 0)
+                    let non_zero_denominator_i = builder.not(is_zero_denominator_i);
+                    cp_is_not_root_of_unity =
+                        builder.and(cp_is_not_root_of_unity, non_zero_denominator_i);
+
+                    let safe_denominator_inv_i = builder.inv_nonnative(&safe_denominator_i);
+                    weights.push(builder.mul_nonnative(&safe_denominator_inv_i, root_i));
+                }
+
+                let cp_to_the_width = BLS12381Scalar::pow_to_const(builder, x, N);
+                let cp_to_the_width_minus_one =
+                    builder.sub_nonnative(&cp_to_the_width, &one_nonnative);
+                let width_inv = BLS12381Scalar::constant(builder, domain.size_inv());
+                let factor = builder.mul_nonnative(&cp_to_the_width_minus_one, &width_inv);
+
+                Self {
+                    weights,
+                    factor,
+                    is_domain_point,
+                    cp_is_not_root_of_unity,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The blobs of one blob-carrying transaction, all evaluated at the same challenge point.
+pub struct MultiBlobPolynomial<const N: usize = BLOB_WIDTH>(Vec<BlobPolynomial<N>>);
+
+impl<const N: usize> MultiBlobPolynomial<N> {
+    pub fn new(builder: &mut Builder, num_blobs: usize) -> Self {
+        assert!(num_blobs > 0);
+        MultiBlobPolynomial(
+            (0..num_blobs)
+                .map(|_| BlobPolynomial::new(builder))
+                .collect(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BlobPolynomial<N>> {
+        self.0.iter()
+    }
+
+    /// One [`BlobPolynomial::commit`] per blob.
+    pub fn commit<H: AlgebraicHasher<F>>(&self, builder: &mut Builder) -> Vec<HashOutTarget> {
+        self.0
+            .iter()
+            .map(|blob| blob.commit::<H>(builder))
+            .collect()
+    }
+
+    /// Evaluates every blob at ``x``, sharing one set of [`BarycentricWeights`].
+    pub fn eval_at(
+        &self,
+        builder: &mut Builder,
+        x: &NonNativeTarget<BLS12381Scalar>,
+    ) -> Vec<NonNativeTarget<BLS12381Scalar>> {
+        let weights = BarycentricWeights::<N>::new(builder, x);
+        self.0
+            .iter()
+            .map(|blob| blob.eval_with_weights(builder, &weights))
+            .collect()
+    }
+
+    /// Evaluates every blob at every point in ``xs``, indexed by point, then blob.
+    pub fn eval_at_many(
+        &self,
+        builder: &mut Builder,
+        xs: &[NonNativeTarget<BLS12381Scalar>],
+    ) -> Vec<Vec<NonNativeTarget<BLS12381Scalar>>> {
+        BarycentricWeights::<N>::new_many(builder, xs)
+            .iter()
+            .map(|weights| {
+                self.0
+                    .iter()
+                    .map(|blob| blob.eval_with_weights(builder, weights))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Native (out-of-circuit) counterpart of [`BlobPolynomial::eval_at`].
+///
+/// Evaluates the blob ``f`` at ``z`` over the same bit-reversed domain, returning ``blob[i]``
+/// when ``z`` is the i'th domain element and the barycentric formula otherwise.
+pub fn eval_at_native<const N: usize>(
+    blob: &[BLS12381Scalar; N],
+    z: BLS12381Scalar,
+) -> BLS12381Scalar {
+    const { assert!(N.is_power_of_two(), "blob width must be a power of two") };
+
+    let domain = blob_domain(N);
+    if let Some(i) = domain.index_of(&z) {
+        return blob[i];
+    }
+
+    let barycentric_evaluation: BLS12381Scalar = blob
+        .iter()
+        .zip(domain.roots().iter())
+        .map(|(coeff, root)| *coeff * *root / (z - *root))
+        .sum();
+
+    barycentric_evaluation * (z.exp_u64(N as u64) - BLS12381Scalar::ONE) * domain.size_inv()
+}
+
+/// Native counterpart of [`BlobPolynomial::commit`], hashing the 8 u32 limbs of every canonical
+/// blob element, least significant first. The blob width is the length of ``blob``.
+pub fn blob_commitment_native<H: AlgebraicHasher<F>>(blob: &[BLS12381Scalar]) -> HashOut<F> {
+    H::hash_no_pad(
+        &blob
+            .iter()
+            .flat_map(|coeff| coeff.to_raw())
+            .flat_map(|limb| [limb as u32, (limb >> 32) as u32])
+            .map(F::from_canonical_u32)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Native counterpart of [`BlobPolynomial::commit_packed`].
+pub fn blob_commitment_packed_native<H: AlgebraicHasher<F>>(blob: &[BLS12381Scalar]) -> HashOut<F> {
+    // each u64 limb is exactly one (lo, hi) pair of u32 limbs
+    let limb_pairs = blob
+        .iter()
+        .flat_map(|coeff| coeff.to_raw())
+        .collect::<Vec<_>>();
+
+    let packed_limbs = limb_pairs
+        .iter()
+        .map(|pair| F::from_noncanonical_u64(*pair));
+    let packed_wrap_flags = limb_pairs.chunks(PACKED_FLAGS_PER_ELEMENT).map(|pairs| {
+        F::from_canonical_u64(
+            pairs
+                .iter()
+                .enumerate()
+                .filter(|(_, pair)| (*pair >> 32) as u32 == u32::MAX)
+                .map(|(i, _)| 1u64 << i)
+                .sum(),
+        )
+    });
+
+    H::hash_no_pad(&packed_limbs.chain(packed_wrap_flags).collect::<Vec<_>>())
+}
+
+/// Native counterpart of [`BlobPolynomial::to_bytes_be`].
+pub fn blob_to_bytes_be(blob: &[BLS12381Scalar]) -> Vec<u8> {
+    blob.iter()
+        .flat_map(|coeff| coeff.to_raw().into_iter().rev())
+        .flat_map(|limb| limb.to_be_bytes())
+        .collect()
+}
+
+/// Native counterpart of [`BlobPolynomial::commit_bytes`], returning the raw digest.
+pub fn blob_commitment_bytes_native(blob: &[BLS12381Scalar], hash: ByteHash) -> [u8; DIGEST_BYTES] {
+    hash.hash(&blob_to_bytes_be(blob))
+}
+
+#[cfg(test)]
+mod tests {
+    use circuit::bigint::biguint::{CircuitBuilderBiguint, WitnessBigUint};
+    use circuit::nonnative::CircuitBuilderNonNative;
+    use circuit::poseidon2::hash::Poseidon2Hash;
+    use circuit::types::config::{Builder, C, CIRCUIT_CONFIG};
+    use num::BigUint;
+    use plonky2::field::types::{Field, PrimeField, Sample};
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{
+        blob_commitment_bytes_native, blob_commitment_packed_native, eval_at_native,
+        BlobPolynomial, MultiBlobPolynomial,
+    };
+    use crate::blob_domain::blob_domain;
+    use crate::bls12_381_scalar_field::{BLS12381Scalar, BLS12_381_SCALAR_LIMBS};
+    use crate::byte_hashes::ByteHash;
+    use crate::file_utils::{read_blob, read_bls1_381_scalar};
+    use crate::kzg::blob_to_scalars;
+
+    /// Proves `eval_at` of a random blob of width ``N`` at ``z`` and checks the result against
+    /// `eval_at_native`.
+    fn check_eval_at<const N: usize>(rng: &mut StdRng, z: BLS12381Scalar) {
+        let blob: [BLS12381Scalar; N] = std::array::from_fn(|_| BLS12381Scalar::sample(rng));
+
+        let mut builder = Builder::new(CircuitConfig {
+            num_wires: 136,
+            ..CIRCUIT_CONFIG
+        });
+        let blob_polynomial = BlobPolynomial::<N>::new(&mut builder);
+        let x = builder.add_virtual_nonnative_target_sized(BLS12_381_SCALAR_LIMBS);
+        let y = blob_polynomial.eval_at(&mut builder, &x);
+        let expected_y = BLS12381Scalar::constant(&mut builder, eval_at_native(&blob, z));
+        builder.connect_biguint(&y.value, &expected_y.value);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (coeff, coeff_target) in blob.iter().zip(blob_polynomial.iter()) {
+            pw.set_biguint_target(&coeff_target.value, &coeff.to_canonical_biguint());
+        }
+        pw.set_biguint_target(&x.value, &z.to_canonical_biguint());
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    /// Same as [`check_eval_at`] for ``num_blobs`` blobs evaluated with one set of shared
+    /// barycentric weights by `MultiBlobPolynomial::eval_at`.
+    fn check_multi_blob_eval_at<const N: usize>(
+        rng: &mut StdRng,
+        num_blobs: usize,
+        z: BLS12381Scalar,
+    ) {
+        let blobs = (0..num_blobs)
+            .map(|_| std::array::from_fn::<_, N, _>(|_| BLS12381Scalar::sample(rng)))
+            .collect::<Vec<_>>();
+
+        let mut builder = Builder::new(CircuitConfig {
+            num_wires: 136,
+            ..CIRCUIT_CONFIG
+        });
+        let blob_polynomials = MultiBlobPolynomial::<N>::new(&mut builder, num_blobs);
+        let x = builder.add_virtual_nonnative_target_sized(BLS12_381_SCALAR_LIMBS);
+        let ys = blob_polynomials.eval_at(&mut builder, &x);
+        assert_eq!(ys.len(), num_blobs);
+        for (y, blob) in ys.iter().zip(blobs.iter()) {
+            let expected_y = BLS12381Scalar::constant(&mut builder, eval_at_native(blob, z));
+            builder.connect_biguint(&y.value, &expected_y.value);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (blob, blob_polynomial) in blobs.iter().zip(blob_polynomials.iter()) {
+            for (coeff, coeff_target) in blob.iter().zip(blob_polynomial.iter()) {
+                pw.set_biguint_target(&coeff_target.value, &coeff.to_canonical_biguint());
+            }
+        }
+        pw.set_biguint_target(&x.value, &z.to_canonical_biguint());
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn commit_packed_matches_native() {
+        let mut rng = StdRng::seed_from_u64(15);
+        let mut blob: [BLS12381Scalar; 16] =
+            std::array::from_fn(|_| BLS12381Scalar::sample(&mut rng));
+        // limb pairs with a high limb of 2^32 - 1, which wrap around the Goldilocks order
+        blob[0] = BLS12381Scalar::from_raw([u64::MAX, u64::MAX, u64::MAX, 0]);
+        blob[1] = BLS12381Scalar::from_raw([0xffff_ffff_0000_0005, 0, 0, 0]);
+        blob[2] = BLS12381Scalar::NEG_ONE;
+
+        let mut builder = Builder::new(CircuitConfig {
+            num_wires: 136,
+            ..CIRCUIT_CONFIG
+        });
+        let blob_polynomial = BlobPolynomial::<16>::new(&mut builder);
+        let commitment = blob_polynomial.commit_packed::<Poseidon2Hash>(&mut builder);
+        let expected_commitment =
+            builder.constant_hash(blob_commitment_packed_native::<Poseidon2Hash>(&blob));
+        builder.connect_hashes(commitment, expected_commitment);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (coeff, coeff_target) in blob.iter().zip(blob_polynomial.iter()) {
+            pw.set_biguint_target(&coeff_target.value, &coeff.to_canonical_biguint());
+        }
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+
+        // (5, 2^32 - 1) and (4, 0) pack to the same element, only the wrap flags tell them apart
+        let mut colliding_blob = blob;
+        colliding_blob[1] = BLS12381Scalar::from_raw([4, 0, 0, 0]);
+        assert_ne!(
+            blob_commitment_packed_native::<Poseidon2Hash>(&colliding_blob),
+            blob_commitment_packed_native::<Poseidon2Hash>(&blob)
+        );
+    }
+
+    #[test]
+    fn commit_bytes_matches_native() {
+        let mut rng = StdRng::seed_from_u64(0x16b);
+        let blob: [BLS12381Scalar; 16] = std::array::from_fn(|_| BLS12381Scalar::sample(&mut rng));
+
+        for hash in [ByteHash::Keccak256, ByteHash::Sha256] {
+            let mut builder = Builder::new(CircuitConfig {
+                num_wires: 136,
+                ..CIRCUIT_CONFIG
+            });
+            let blob_polynomial = BlobPolynomial::<16>::new(&mut builder);
+            let commitment = blob_polynomial.commit_bytes(&mut builder, hash);
+            let expected_commitment = builder.constant_biguint(&BigUint::from_bytes_be(
+                &blob_commitment_bytes_native(&blob, hash),
+            ));
+            builder.connect_biguint(&commitment, &expected_commitment);
+            let data = builder.build::<C>();
+
+            let mut pw = PartialWitness::new();
+            for (coeff, coeff_target) in blob.iter().zip(blob_polynomial.iter()) {
+                pw.set_biguint_target(&coeff_target.value, &coeff.to_canonical_biguint());
+            }
+            let proof = data.prove(pw).unwrap();
+            data.verify(proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn eval_at_native_matches_fixture() {
+        let blob = blob_to_scalars(&read_blob());
+        let x = BLS12381Scalar::from_noncanonical_biguint(read_bls1_381_scalar("x"));
+        let y = BLS12381Scalar::from_noncanonical_biguint(read_bls1_381_scalar("y"));
+        assert_eq!(eval_at_native(&blob, x), y);
+    }
+
+    #[test]
+    fn eval_at_matches_native_16() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let z = BLS12381Scalar::sample(&mut rng);
+        check_eval_at::<16>(&mut rng, z);
+        check_eval_at::<16>(&mut rng, blob_domain(16).root(3));
+    }
+
+    #[test]
+    fn eval_at_matches_native_256() {
+        let mut rng = StdRng::seed_from_u64(256);
+        let z = BLS12381Scalar::sample(&mut rng);
+        check_eval_at::<256>(&mut rng, z);
+        check_eval_at::<256>(&mut rng, blob_domain(256).root(255));
+    }
+
+    #[test]
+    fn multi_blob_eval_at_matches_native_16() {
+        let mut rng = StdRng::seed_from_u64(0x16);
+        let z = BLS12381Scalar::sample(&mut rng);
+        check_multi_blob_eval_at::<16>(&mut rng, 3, z);
+        check_multi_blob_eval_at::<16>(&mut rng, 3, blob_domain(16).root(5));
+    }
+}
@@ -9,23 +9,76 @@ use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAss
 use circuit::bigint::biguint::CircuitBuilderBiguint;
 use circuit::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
 use circuit::types::config::Builder;
+use circuit::u32::gadgets::arithmetic_u32::CircuitBuilderU32;
 use itertools::Itertools;
 use num::bigint::BigUint;
-use num::{Integer, One};
 use plonky2::field::types::{Field, PrimeField, Sample};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-/// The base field of the bls12381 elliptic curve.
+/// The scalar field of the bls12381 elliptic curve.
 ///
 /// Its order is
 /// ```ignore
 /// P = 0x73EDA753 299D7D48 3339D808 09A1D805 53BDA402 FFFE5BFE FFFFFFFF 00000001
 /// ```
-#[derive(Copy, Clone, Serialize, Deserialize)]
-pub struct BLS12381Scalar(pub [u64; 4]);
+///
+/// Elements are stored as four little-endian 64-bit limbs in Montgomery form, i.e. `a` is
+/// represented by `a * R mod P` with `R = 2^256`, and are always kept fully reduced. The limbs are
+/// private; use [`BLS12381Scalar::from_raw`] and [`BLS12381Scalar::to_raw`] to go to and from the
+/// canonical limbs, which is also the form used by serde.
+#[derive(Copy, Clone)]
+pub struct BLS12381Scalar([u64; 4]);
 
 pub const BLS12_381_SCALAR_LIMBS: usize = 8;
 
+/// P, the order of the field.
+const MODULUS: [u64; 4] = [
+    0xffff_ffff_0000_0001,
+    0x53bd_a402_fffe_5bfe,
+    0x3339_d808_09a1_d805,
+    0x73ed_a753_299d_7d48,
+];
+
+/// INV = -(P^{-1} mod 2^64) mod 2^64
+const INV: u64 = 0xffff_fffe_ffff_ffff;
+
+/// R = 2^256 mod P
+const R: [u64; 4] = [
+    0x0000_0001_ffff_fffe,
+    0x5884_b7fa_0003_4802,
+    0x998c_4fef_ecbc_4ff5,
+    0x1824_b159_acc5_056f,
+];
+
+/// R^2 = 2^512 mod P
+const R2: [u64; 4] = [
+    0xc999_e990_f3f2_9c6d,
+    0x2b6c_edcb_8792_5c23,
+    0x05d3_1496_7254_398f,
+    0x0748_d9d9_9f59_ff11,
+];
+
+/// Compute a + b + carry, returning the result and the new carry over.
+#[inline(always)]
+const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let ret = (a as u128) + (b as u128) + (carry as u128);
+    (ret as u64, (ret >> 64) as u64)
+}
+
+/// Compute a - (b + borrow), returning the result and the new borrow.
+#[inline(always)]
+const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let ret = (a as u128).wrapping_sub((b as u128) + ((borrow >> 63) as u128));
+    (ret as u64, (ret >> 64) as u64)
+}
+
+/// Compute a + (b * c) + carry, returning the result and the new carry over.
+#[inline(always)]
+const fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let ret = (a as u128) + ((b as u128) * (c as u128)) + (carry as u128);
+    (ret as u64, (ret >> 64) as u64)
+}
+
 impl BLS12381Scalar {
     pub fn divide(
         builder: &mut Builder,
@@ -36,6 +89,17 @@ impl BLS12381Scalar {
         builder.mul_nonnative(numerator, &denominator_inv)
     }
 
+    /// The constant ``c`` as a non-native target, padded to [`BLS12_381_SCALAR_LIMBS`] limbs.
+    pub fn constant(builder: &mut Builder, c: Self) -> NonNativeTarget<Self> {
+        let mut c_big = builder.constant_biguint(&c.to_canonical_biguint());
+        if c_big.limbs.len() < BLS12_381_SCALAR_LIMBS {
+            c_big
+                .limbs
+                .resize(BLS12_381_SCALAR_LIMBS, builder.zero_u32());
+        }
+        builder.biguint_to_nonnative(&c_big)
+    }
+
     pub fn pow_to_const(
         builder: &mut Builder,
         x: &NonNativeTarget<Self>,
@@ -58,31 +122,255 @@ impl BLS12381Scalar {
     }
 }
 
+impl BLS12381Scalar {
+    /// Converts four little-endian 64-bit limbs into Montgomery form. The input does not need to
+    /// be reduced.
+    #[inline]
+    pub const fn from_raw(val: [u64; 4]) -> Self {
+        Self(val).mont_mul(&Self(R2))
+    }
+
+    /// Returns the canonical little-endian 64-bit limbs of this element.
+    #[inline]
+    pub const fn to_raw(&self) -> [u64; 4] {
+        Self::montgomery_reduce(self.0[0], self.0[1], self.0[2], self.0[3], 0, 0, 0, 0).0
+    }
+
+    #[inline]
+    const fn mont_add(&self, rhs: &Self) -> Self {
+        let (d0, carry) = adc(self.0[0], rhs.0[0], 0);
+        let (d1, carry) = adc(self.0[1], rhs.0[1], carry);
+        let (d2, carry) = adc(self.0[2], rhs.0[2], carry);
+        let (d3, _) = adc(self.0[3], rhs.0[3], carry);
+
+        // Both operands are below P < 2^255, so the sum fits in 256 bits and a single
+        // conditional subtraction brings it back into range.
+        Self([d0, d1, d2, d3]).mont_sub(&Self(MODULUS))
+    }
+
+    #[inline]
+    const fn mont_sub(&self, rhs: &Self) -> Self {
+        let (d0, borrow) = sbb(self.0[0], rhs.0[0], 0);
+        let (d1, borrow) = sbb(self.0[1], rhs.0[1], borrow);
+        let (d2, borrow) = sbb(self.0[2], rhs.0[2], borrow);
+        let (d3, borrow) = sbb(self.0[3], rhs.0[3], borrow);
+
+        // If underflow occurred, `borrow` is all ones and P is added back; otherwise this adds
+        // zero.
+        let (d0, carry) = adc(d0, MODULUS[0] & borrow, 0);
+        let (d1, carry) = adc(d1, MODULUS[1] & borrow, carry);
+        let (d2, carry) = adc(d2, MODULUS[2] & borrow, carry);
+        let (d3, _) = adc(d3, MODULUS[3] & borrow, carry);
+
+        Self([d0, d1, d2, d3])
+    }
+
+    #[inline]
+    const fn mont_neg(&self) -> Self {
+        let (d0, borrow) = sbb(MODULUS[0], self.0[0], 0);
+        let (d1, borrow) = sbb(MODULUS[1], self.0[1], borrow);
+        let (d2, borrow) = sbb(MODULUS[2], self.0[2], borrow);
+        let (d3, _) = sbb(MODULUS[3], self.0[3], borrow);
+
+        // Zero must map to zero rather than to P.
+        let mask = (((self.0[0] | self.0[1] | self.0[2] | self.0[3]) == 0) as u64).wrapping_sub(1);
+
+        Self([d0 & mask, d1 & mask, d2 & mask, d3 & mask])
+    }
+
+    #[inline]
+    const fn mont_mul(&self, rhs: &Self) -> Self {
+        // Schoolbook multiplication
+        let (r0, carry) = mac(0, self.0[0], rhs.0[0], 0);
+        let (r1, carry) = mac(0, self.0[0], rhs.0[1], carry);
+        let (r2, carry) = mac(0, self.0[0], rhs.0[2], carry);
+        let (r3, r4) = mac(0, self.0[0], rhs.0[3], carry);
+
+        let (r1, carry) = mac(r1, self.0[1], rhs.0[0], 0);
+        let (r2, carry) = mac(r2, self.0[1], rhs.0[1], carry);
+        let (r3, carry) = mac(r3, self.0[1], rhs.0[2], carry);
+        let (r4, r5) = mac(r4, self.0[1], rhs.0[3], carry);
+
+        let (r2, carry) = mac(r2, self.0[2], rhs.0[0], 0);
+        let (r3, carry) = mac(r3, self.0[2], rhs.0[1], carry);
+        let (r4, carry) = mac(r4, self.0[2], rhs.0[2], carry);
+        let (r5, r6) = mac(r5, self.0[2], rhs.0[3], carry);
+
+        let (r3, carry) = mac(r3, self.0[3], rhs.0[0], 0);
+        let (r4, carry) = mac(r4, self.0[3], rhs.0[1], carry);
+        let (r5, carry) = mac(r5, self.0[3], rhs.0[2], carry);
+        let (r6, r7) = mac(r6, self.0[3], rhs.0[3], carry);
+
+        Self::montgomery_reduce(r0, r1, r2, r3, r4, r5, r6, r7)
+    }
+
+    /// Reduces the 512-bit value `r0 + r1 * 2^64 + ... + r7 * 2^448` to `value * R^{-1} mod P`.
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    const fn montgomery_reduce(
+        r0: u64,
+        r1: u64,
+        r2: u64,
+        r3: u64,
+        r4: u64,
+        r5: u64,
+        r6: u64,
+        r7: u64,
+    ) -> Self {
+        let k = r0.wrapping_mul(INV);
+        let (_, carry) = mac(r0, k, MODULUS[0], 0);
+        let (r1, carry) = mac(r1, k, MODULUS[1], carry);
+        let (r2, carry) = mac(r2, k, MODULUS[2], carry);
+        let (r3, carry) = mac(r3, k, MODULUS[3], carry);
+        let (r4, carry2) = adc(r4, 0, carry);
+
+        let k = r1.wrapping_mul(INV);
+        let (_, carry) = mac(r1, k, MODULUS[0], 0);
+        let (r2, carry) = mac(r2, k, MODULUS[1], carry);
+        let (r3, carry) = mac(r3, k, MODULUS[2], carry);
+        let (r4, carry) = mac(r4, k, MODULUS[3], carry);
+        let (r5, carry2) = adc(r5, carry2, carry);
+
+        let k = r2.wrapping_mul(INV);
+        let (_, carry) = mac(r2, k, MODULUS[0], 0);
+        let (r3, carry) = mac(r3, k, MODULUS[1], carry);
+        let (r4, carry) = mac(r4, k, MODULUS[2], carry);
+        let (r5, carry) = mac(r5, k, MODULUS[3], carry);
+        let (r6, carry2) = adc(r6, carry2, carry);
+
+        let k = r3.wrapping_mul(INV);
+        let (_, carry) = mac(r3, k, MODULUS[0], 0);
+        let (r4, carry) = mac(r4, k, MODULUS[1], carry);
+        let (r5, carry) = mac(r5, k, MODULUS[2], carry);
+        let (r6, carry) = mac(r6, k, MODULUS[3], carry);
+        let (r7, _) = adc(r7, carry2, carry);
+
+        // The result is below 2P, so one conditional subtraction fully reduces it.
+        Self([r4, r5, r6, r7]).mont_sub(&Self(MODULUS))
+    }
+
+    /// Returns whether ``limbs`` is below P, i.e. whether it is the canonical form of an element.
+    #[inline]
+    pub const fn is_canonical(limbs: &[u64; 4]) -> bool {
+        !geq(limbs, &MODULUS)
+    }
+
+    /// Inverts a non-zero element with the binary extended Euclidean algorithm.
+    ///
+    /// It runs on the canonical limbs, keeping `u = x1 * a` and `v = x2 * a` (mod P) while halving
+    /// and subtracting `u` and `v` down until one of them reaches 1. That takes about `2 * 255`
+    /// shifts and subtractions of 256-bit integers, against the 255 squarings and ~128
+    /// multiplications of exponentiating by `P - 2`. Unlike the exponentiation, its running time
+    /// depends on `self`.
+    fn binary_inverse(&self) -> Self {
+        let a = self.to_raw();
+        debug_assert!(a != [0; 4]);
+
+        let (mut u, mut v) = (a, MODULUS);
+        let (mut x1, mut x2) = ([1, 0, 0, 0], [0; 4]);
+        while u != [1, 0, 0, 0] && v != [1, 0, 0, 0] {
+            while u[0] & 1 == 0 {
+                u = shr1(&u);
+                x1 = half_mod(&x1);
+            }
+            while v[0] & 1 == 0 {
+                v = shr1(&v);
+                x2 = half_mod(&x2);
+            }
+
+            // Both are odd now, so their difference is even and the next round halves it again.
+            // All of `u`, `v`, `x1` and `x2` stay in `[0, P]`, so `mont_sub` is plain subtraction
+            // mod P on them.
+            if geq(&u, &v) {
+                u = Self(u).mont_sub(&Self(v)).0;
+                x1 = Self(x1).mont_sub(&Self(x2)).0;
+            } else {
+                v = Self(v).mont_sub(&Self(u)).0;
+                x2 = Self(x2).mont_sub(&Self(x1)).0;
+            }
+        }
+
+        Self::from_raw(if u == [1, 0, 0, 0] { x1 } else { x2 })
+    }
+}
+
+/// Returns whether ``a >= b``.
+#[inline]
+const fn geq(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    let (_, borrow) = sbb(a[0], b[0], 0);
+    let (_, borrow) = sbb(a[1], b[1], borrow);
+    let (_, borrow) = sbb(a[2], b[2], borrow);
+    let (_, borrow) = sbb(a[3], b[3], borrow);
+
+    borrow == 0
+}
+
+/// Shifts ``a`` right by one bit.
+#[inline]
+const fn shr1(a: &[u64; 4]) -> [u64; 4] {
+    [
+        (a[0] >> 1) | (a[1] << 63),
+        (a[1] >> 1) | (a[2] << 63),
+        (a[2] >> 1) | (a[3] << 63),
+        a[3] >> 1,
+    ]
+}
+
+/// Computes ``a / 2 mod P`` for a reduced ``a``. Odd values get P added first, which cannot
+/// overflow since P < 2^255.
+#[inline]
+const fn half_mod(a: &[u64; 4]) -> [u64; 4] {
+    if a[0] & 1 == 0 {
+        return shr1(a);
+    }
+
+    let (d0, carry) = adc(a[0], MODULUS[0], 0);
+    let (d1, carry) = adc(a[1], MODULUS[1], carry);
+    let (d2, carry) = adc(a[2], MODULUS[2], carry);
+    let (d3, _) = adc(a[3], MODULUS[3], carry);
+
+    shr1(&[d0, d1, d2, d3])
+}
+
 impl Field for BLS12381Scalar {
     const ZERO: Self = Self([0; 4]);
-    const ONE: Self = Self([1, 0, 0, 0]);
-    const TWO: Self = Self([2, 0, 0, 0]);
+    const ONE: Self = Self(R);
+    const TWO: Self = Self([
+        0x0000_0003_ffff_fffc,
+        0xb109_6ff4_0006_9004,
+        0x3318_9fdf_d978_9fea,
+        0x3049_62b3_598a_0adf,
+    ]);
     const NEG_ONE: Self = Self([
-        0xFFFFFFFF00000000,
-        0x53BDA402FFFE5BFE,
-        0x3339D80809A1D805,
-        0x73EDA753299D7D48,
+        0xffff_fffd_0000_0003,
+        0xfb38_ec08_fffb_13fc,
+        0x99ad_8818_1ce5_880f,
+        0x5bc8_f5f9_7cd8_77d8,
     ]);
 
-    const TWO_ADICITY: usize = 32; // irfan try 32
+    // P - 1 = 2^32 * t with t odd.
+    const TWO_ADICITY: usize = 32;
     const CHARACTERISTIC_TWO_ADICITY: usize = Self::TWO_ADICITY;
 
-    // Sage: `g = GF(p).multiplicative_generator()`
-    const MULTIPLICATIVE_GROUP_GENERATOR: Self = Self([7, 0, 0, 0]);
-
-    // Sage: `g_2 = power_mod(g, (p - 1) // 2^6), p)`
-    const POWER_OF_TWO_GENERATOR: Self = Self([
+    // Sage: `g = GF(p).multiplicative_generator()`, i.e. 7 in Montgomery form.
+    const MULTIPLICATIVE_GROUP_GENERATOR: Self = Self([
         0x0000_000e_ffff_fff1,
         0x17e3_63d3_0018_9c0f,
         0xff9c_5787_6f84_57b0,
         0x3513_3220_8fc5_a8c4,
     ]);
 
+    // Sage: `g_2 = power_mod(g, (p - 1) // 2^32, p)`, i.e.
+    // 0x16a2a19edfe81f20d09b681922c813b4b63683508c2280b93829971f439f0d2b in Montgomery form.
+    // Raising it to 2^20 gives `BlobDomain::GENERATOR`, the 4096'th root of unity, and in general
+    // to 2^(32 - k) the generator of the 2^k domain.
+    const POWER_OF_TWO_GENERATOR: Self = Self([
+        0xb9b5_8d8c_5f0e_466a,
+        0x5b1b_4c80_1819_d7ec,
+        0x0af5_3ae3_52a3_1e64,
+        0x5bf3_adda_19e9_b27b,
+    ]);
+
     const BITS: usize = 255;
 
     fn order() -> BigUint {
@@ -100,13 +388,13 @@ impl Field for BLS12381Scalar {
             return None;
         }
 
-        // Fermat's Little Theorem
-        Some(self.exp_biguint(&(Self::order() - BigUint::one() - BigUint::one())))
+        Some(self.binary_inverse())
     }
 
     fn from_noncanonical_biguint(val: BigUint) -> Self {
-        Self(
-            val.to_u64_digits()
+        Self::from_raw(
+            (val % Self::order())
+                .to_u64_digits()
                 .into_iter()
                 .pad_using(4, |_| 0)
                 .collect::<Vec<_>>()[..]
@@ -117,17 +405,17 @@ impl Field for BLS12381Scalar {
 
     #[inline]
     fn from_canonical_u64(n: u64) -> Self {
-        Self([n, 0, 0, 0])
+        Self::from_raw([n, 0, 0, 0])
     }
 
     #[inline]
     fn from_noncanonical_u128(n: u128) -> Self {
-        Self([n as u64, (n >> 64) as u64, 0, 0])
+        Self::from_raw([n as u64, (n >> 64) as u64, 0, 0])
     }
 
     #[inline]
     fn from_noncanonical_u96(n: (u64, u32)) -> Self {
-        Self([n.0, n.1 as u64, 0, 0])
+        Self::from_raw([n.0, n.1 as u64, 0, 0])
     }
 
     fn from_noncanonical_i64(n: i64) -> Self {
@@ -165,7 +453,7 @@ impl Default for BLS12381Scalar {
 
 impl PartialEq for BLS12381Scalar {
     fn eq(&self, other: &Self) -> bool {
-        self.to_canonical_biguint() == other.to_canonical_biguint()
+        self.0 == other.0
     }
 }
 
@@ -173,7 +461,25 @@ impl Eq for BLS12381Scalar {}
 
 impl Hash for BLS12381Scalar {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.to_canonical_biguint().hash(state)
+        self.0.hash(state)
+    }
+}
+
+impl Serialize for BLS12381Scalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_raw().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BLS12381Scalar {
+    /// Rejects limbs that are not below P: reducing them would make two different encodings
+    /// deserialize to the same element.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let limbs = <[u64; 4]>::deserialize(deserializer)?;
+        if !Self::is_canonical(&limbs) {
+            return Err(serde::de::Error::custom("non-canonical BLS12-381 scalar"));
+        }
+        Ok(Self::from_raw(limbs))
     }
 }
 
@@ -202,11 +508,7 @@ impl Sample for BLS12381Scalar {
 
 impl PrimeField for BLS12381Scalar {
     fn to_canonical_biguint(&self) -> BigUint {
-        let mut result = biguint_from_array(self.0);
-        if result >= Self::order() {
-            result -= Self::order();
-        }
-        result
+        biguint_from_array(self.to_raw())
     }
 }
 
@@ -215,11 +517,7 @@ impl Neg for BLS12381Scalar {
 
     #[inline]
     fn neg(self) -> Self {
-        if self.is_zero() {
-            Self::ZERO
-        } else {
-            Self::from_noncanonical_biguint(Self::order() - self.to_canonical_biguint())
-        }
+        self.mont_neg()
     }
 }
 
@@ -228,11 +526,7 @@ impl Add for BLS12381Scalar {
 
     #[inline]
     fn add(self, rhs: Self) -> Self {
-        let mut result = self.to_canonical_biguint() + rhs.to_canonical_biguint();
-        if result >= Self::order() {
-            result -= Self::order();
-        }
-        Self::from_noncanonical_biguint(result)
+        self.mont_add(&rhs)
     }
 }
 
@@ -253,9 +547,8 @@ impl Sub for BLS12381Scalar {
     type Output = Self;
 
     #[inline]
-    #[allow(clippy::suspicious_arithmetic_impl)]
     fn sub(self, rhs: Self) -> Self {
-        self + -rhs
+        self.mont_sub(&rhs)
     }
 }
 
@@ -271,9 +564,7 @@ impl Mul for BLS12381Scalar {
 
     #[inline]
     fn mul(self, rhs: Self) -> Self {
-        Self::from_noncanonical_biguint(
-            (self.to_canonical_biguint() * rhs.to_canonical_biguint()).mod_floor(&Self::order()),
-        )
+        self.mont_mul(&rhs)
     }
 }
 
@@ -305,3 +596,236 @@ impl DivAssign for BLS12381Scalar {
         *self = *self / rhs;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::BigUint;
+    use plonky2::field::ops::Square;
+    use plonky2::field::types::{Field, PrimeField, Sample};
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    use super::{biguint_from_array, BLS12381Scalar, MODULUS, R, R2};
+    use crate::kzg::to_bls_scalar;
+
+    const NUM_SAMPLES: usize = 256;
+
+    /// Random elements together with the values next to the edges of the field, where carries and
+    /// borrows wrap around.
+    fn test_inputs() -> Vec<BLS12381Scalar> {
+        let mut rng = StdRng::seed_from_u64(0x0b15_1238);
+        let mut inputs = vec![
+            BLS12381Scalar::ZERO,
+            BLS12381Scalar::ONE,
+            BLS12381Scalar::TWO,
+            BLS12381Scalar::NEG_ONE,
+            BLS12381Scalar::NEG_ONE - BLS12381Scalar::ONE,
+            BLS12381Scalar::from_noncanonical_biguint(BigUint::from(1u8) << 254),
+            BLS12381Scalar::from_canonical_u64(u64::MAX),
+        ];
+        inputs.extend((0..NUM_SAMPLES).map(|_| BLS12381Scalar::sample(&mut rng)));
+        inputs
+    }
+
+    #[test]
+    fn arithmetic_matches_bls12_381() {
+        let inputs = test_inputs();
+        for (&a, &b) in inputs.iter().zip(inputs.iter().rev()) {
+            let (a_bls, b_bls) = (to_bls_scalar(&a), to_bls_scalar(&b));
+            assert_eq!(to_bls_scalar(&(a + b)), a_bls + b_bls);
+            assert_eq!(to_bls_scalar(&(a - b)), a_bls - b_bls);
+            assert_eq!(to_bls_scalar(&(a * b)), a_bls * b_bls);
+            assert_eq!(to_bls_scalar(&a.square()), a_bls.square());
+            assert_eq!(to_bls_scalar(&-a), -a_bls);
+        }
+    }
+
+    #[test]
+    fn inverse_matches_bls12_381() {
+        for a in test_inputs() {
+            let a_bls = to_bls_scalar(&a);
+            match a.try_inverse() {
+                None => assert!(bool::from(a_bls.invert().is_none())),
+                Some(a_inv) => {
+                    assert_eq!(to_bls_scalar(&a_inv), a_bls.invert().unwrap());
+                    assert_eq!(a * a_inv, BLS12381Scalar::ONE);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn primitive_root_of_unity_matches_bls12_381() {
+        let generator = bls12_381::Scalar::from(7u64);
+        assert_eq!(
+            to_bls_scalar(&BLS12381Scalar::MULTIPLICATIVE_GROUP_GENERATOR),
+            generator
+        );
+
+        let p_minus_one = BLS12381Scalar::order() - 1u32;
+        for n_log in 0..=BLS12381Scalar::TWO_ADICITY {
+            let mut exponent = [0u64; 4];
+            for (limb, digit) in exponent
+                .iter_mut()
+                .zip((&p_minus_one >> n_log).to_u64_digits())
+            {
+                *limb = digit;
+            }
+            assert_eq!(
+                to_bls_scalar(&BLS12381Scalar::primitive_root_of_unity(n_log)),
+                generator.pow_vartime(&exponent),
+                "2^{n_log}'th root of unity"
+            );
+        }
+    }
+
+    #[test]
+    fn canonical_biguint_round_trip() {
+        for a in test_inputs() {
+            let a_big = a.to_canonical_biguint();
+            assert!(a_big < BLS12381Scalar::order());
+            assert_eq!(BigUint::from_bytes_le(&to_bls_scalar(&a).to_bytes()), a_big);
+            assert_eq!(BLS12381Scalar::from_noncanonical_biguint(a_big.clone()), a);
+            assert_eq!(
+                BLS12381Scalar::from_noncanonical_biguint(a_big + BLS12381Scalar::order()),
+                a
+            );
+        }
+    }
+
+    #[test]
+    fn montgomery_constants() {
+        let r = BigUint::from(1u8) << 256;
+        assert_eq!(biguint_from_array(MODULUS), BLS12381Scalar::order());
+        assert_eq!(biguint_from_array(R), &r % BLS12381Scalar::order());
+        assert_eq!(biguint_from_array(R2), (&r * &r) % BLS12381Scalar::order());
+
+        // The Montgomery form of `a` is `a * R`, so `from_raw` of 1 gives `R` itself.
+        assert_eq!(BLS12381Scalar::from_raw([1, 0, 0, 0]).0, R);
+        assert_eq!(BLS12381Scalar::ONE.to_raw(), [1, 0, 0, 0]);
+        assert_eq!(BLS12381Scalar::TWO.to_raw(), [2, 0, 0, 0]);
+        assert_eq!(
+            BLS12381Scalar::NEG_ONE.to_canonical_biguint(),
+            BLS12381Scalar::order() - 1u32
+        );
+        assert_eq!(
+            BLS12381Scalar::MULTIPLICATIVE_GROUP_GENERATOR.to_raw(),
+            [7, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn raw_limbs_round_trip() {
+        for a in test_inputs() {
+            let raw = a.to_raw();
+            assert!(BLS12381Scalar::is_canonical(&raw));
+            assert_eq!(BLS12381Scalar::from_raw(raw), a);
+        }
+
+        // `from_raw` reduces its input, so `P` and `P + 1` land on zero and one.
+        assert_eq!(BLS12381Scalar::from_raw(MODULUS), BLS12381Scalar::ZERO);
+        assert!(!BLS12381Scalar::is_canonical(&MODULUS));
+        let mut modulus_plus_one = MODULUS;
+        modulus_plus_one[0] += 1;
+        assert_eq!(
+            BLS12381Scalar::from_raw(modulus_plus_one),
+            BLS12381Scalar::ONE
+        );
+    }
+
+    #[test]
+    fn montgomery_arithmetic_matches_biguint() {
+        let p = BLS12381Scalar::order();
+        let inputs = test_inputs();
+        for (&a, &b) in inputs.iter().zip(inputs.iter().skip(1)) {
+            let (a_big, b_big) = (a.to_canonical_biguint(), b.to_canonical_biguint());
+            assert_eq!((a + b).to_canonical_biguint(), (&a_big + &b_big) % &p);
+            assert_eq!((a - b).to_canonical_biguint(), (&a_big + &p - &b_big) % &p);
+            assert_eq!((a * b).to_canonical_biguint(), (&a_big * &b_big) % &p);
+            assert_eq!(a.square().to_canonical_biguint(), (&a_big * &a_big) % &p);
+            assert_eq!((-a).to_canonical_biguint(), (&p - &a_big) % &p);
+        }
+    }
+
+    #[test]
+    fn binary_inverse_matches_fermat() {
+        let p_minus_two = BLS12381Scalar::order() - 2u32;
+        for a in test_inputs().into_iter().filter(|a| !a.is_zero()) {
+            assert_eq!(a.inverse(), a.exp_biguint(&p_minus_two));
+        }
+        assert_eq!(BLS12381Scalar::ZERO.try_inverse(), None);
+    }
+
+    #[test]
+    fn serde_uses_canonical_limbs() {
+        for a in test_inputs() {
+            let json = serde_json::to_string(&a).unwrap();
+            assert_eq!(json, serde_json::to_string(&a.to_raw()).unwrap());
+            assert_eq!(serde_json::from_str::<BLS12381Scalar>(&json).unwrap(), a);
+        }
+
+        let non_canonical = serde_json::to_string(&MODULUS).unwrap();
+        assert!(serde_json::from_str::<BLS12381Scalar>(&non_canonical).is_err());
+    }
+
+    // The checks of plonky2's `test_field_arithmetic!`, which is only exported to plonky2's own
+    // tests.
+    #[test]
+    fn batch_inversion() {
+        for n in 0..20u64 {
+            let xs = (1..=n)
+                .map(BLS12381Scalar::from_canonical_u64)
+                .collect::<Vec<_>>();
+            let invs = BLS12381Scalar::batch_multiplicative_inverse(&xs);
+            assert_eq!(invs.len(), xs.len());
+            for (x, inv) in xs.into_iter().zip(invs) {
+                assert_eq!(x * inv, BLS12381Scalar::ONE);
+            }
+        }
+    }
+
+    #[test]
+    fn primitive_root_order() {
+        for n_log in 0..8 {
+            let root = BLS12381Scalar::primitive_root_of_unity(n_log);
+            assert_eq!(BLS12381Scalar::generator_order(root), 1 << n_log);
+        }
+    }
+
+    #[test]
+    fn modular_reduction() {
+        let mut rng = StdRng::seed_from_u64(0x0b15_1238);
+        for _ in 0..16 {
+            let (lo, hi) = (rng.next_u64(), rng.next_u32());
+            let x = (lo as u128) + ((hi as u128) << 64);
+            assert_eq!(
+                BLS12381Scalar::from_noncanonical_u128(x),
+                BLS12381Scalar::from_noncanonical_u96((lo, hi))
+            );
+        }
+    }
+
+    #[test]
+    fn exponentiation() {
+        let mut rng = StdRng::seed_from_u64(0x0b15_1238);
+        for x in [
+            BLS12381Scalar::ZERO,
+            BLS12381Scalar::ONE,
+            BLS12381Scalar::TWO,
+        ] {
+            assert_eq!(x.exp_u64(0), BLS12381Scalar::ONE);
+            assert_eq!(x.exp_u64(1), x);
+        }
+
+        let base = BLS12381Scalar::sample(&mut rng);
+        let power = BigUint::from(rng.next_u64());
+        let cycles = rng.next_u32();
+        let group_order = BLS12381Scalar::order() - 1u32;
+        let big_power = &power + &group_order * cycles;
+        assert_eq!(base.exp_biguint(&power), base.exp_biguint(&big_power));
+        assert_ne!(
+            base.exp_biguint(&power),
+            base.exp_biguint(&(big_power + 1u32))
+        );
+    }
+}
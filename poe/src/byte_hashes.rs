@@ -0,0 +1,402 @@
+//! In-circuit Keccak-256 and SHA-256 over byte strings.
+//!
+//! Bytes are represented as 8 [`BoolTarget`]s, least significant bit first, and messages and
+//! digests are flat sequences of such bytes.
+
+use circuit::bigint::biguint::BigUintTarget;
+use circuit::types::config::{Builder, F};
+use circuit::u32::gadgets::arithmetic_u32::U32Target;
+use plonky2::field::types::Field;
+use plonky2::iop::target::BoolTarget;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+pub const DIGEST_BYTES: usize = 32;
+
+const KECCAK_RATE_BYTES: usize = 136;
+const KECCAK_LANES: usize = 25;
+const KECCAK_ROUNDS: usize = 24;
+
+const KECCAK_ROUND_CONSTANTS: [u64; KECCAK_ROUNDS] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// Rotation offsets of the rho step, indexed by `[x][y]`.
+const KECCAK_ROTATIONS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+const SHA256_BLOCK_BYTES: usize = 64;
+
+const SHA256_ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+pub type Byte = [BoolTarget; 8];
+type Lane = [BoolTarget; 64];
+type Word = [BoolTarget; 32];
+
+/// Byte-oriented hash functions, for commitments that have to be recomputed outside of plonky2,
+/// e.g. by an L1 contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteHash {
+    Keccak256,
+    Sha256,
+}
+
+impl ByteHash {
+    pub fn hash_circuit(&self, builder: &mut Builder, message: &[Byte]) -> [Byte; DIGEST_BYTES] {
+        match self {
+            ByteHash::Keccak256 => keccak256_circuit(builder, message),
+            ByteHash::Sha256 => sha256_circuit(builder, message),
+        }
+    }
+
+    pub fn hash(&self, message: &[u8]) -> [u8; DIGEST_BYTES] {
+        match self {
+            ByteHash::Keccak256 => Keccak256::digest(message).into(),
+            ByteHash::Sha256 => Sha256::digest(message).into(),
+        }
+    }
+}
+
+/// Keccak-256 as used by Ethereum, i.e. with the original `0x01` padding rather than SHA-3's.
+pub fn keccak256_circuit(builder: &mut Builder, message: &[Byte]) -> [Byte; DIGEST_BYTES] {
+    // pad10*1
+    let mut padded = message.to_vec();
+    let num_padding_bytes = KECCAK_RATE_BYTES - message.len() % KECCAK_RATE_BYTES;
+    let mut padding = vec![0u8; num_padding_bytes];
+    padding[0] |= 0x01;
+    padding[num_padding_bytes - 1] |= 0x80;
+    padded.extend(padding.into_iter().map(|byte| constant_byte(builder, byte)));
+
+    let mut state: Option<[Lane; KECCAK_LANES]> = None;
+    for block in padded.chunks(KECCAK_RATE_BYTES) {
+        let block_lanes = block.chunks(8).map(bytes_to_lane).collect::<Vec<_>>();
+
+        let mut absorbed = match state {
+            // the initial state is all zeros, so the first block is taken as is
+            None => {
+                let zero_lane = [builder._false(); 64];
+                core::array::from_fn(|i| block_lanes.get(i).copied().unwrap_or(zero_lane))
+            }
+            Some(state) => core::array::from_fn(|i| match block_lanes.get(i) {
+                Some(block_lane) => xor_bits(builder, &state[i], block_lane),
+                None => state[i],
+            }),
+        };
+        keccak_f(builder, &mut absorbed);
+        state = Some(absorbed);
+    }
+
+    let state = state.unwrap();
+    core::array::from_fn(|i| core::array::from_fn(|j| state[i / 8][(i % 8) * 8 + j]))
+}
+
+/// The Keccak-f[1600] permutation on lanes indexed by `x + 5 * y`.
+fn keccak_f(builder: &mut Builder, state: &mut [Lane; KECCAK_LANES]) {
+    for round_constant in KECCAK_ROUND_CONSTANTS {
+        // theta
+        let columns: [Lane; 5] = core::array::from_fn(|x| {
+            (1..5).fold(state[x], |column, y| {
+                xor_bits(builder, &column, &state[x + 5 * y])
+            })
+        });
+        for x in 0..5 {
+            let rotated = rotate_left(&columns[(x + 1) % 5], 1);
+            let d = xor_bits(builder, &columns[(x + 4) % 5], &rotated);
+            for y in 0..5 {
+                state[x + 5 * y] = xor_bits(builder, &state[x + 5 * y], &d);
+            }
+        }
+
+        // rho and pi
+        let mut permuted = *state;
+        for x in 0..5 {
+            for y in 0..5 {
+                permuted[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    rotate_left(&state[x + 5 * y], KECCAK_ROTATIONS[x][y]);
+            }
+        }
+
+        // chi
+        for x in 0..5 {
+            for y in 0..5 {
+                let b0 = &permuted[x + 5 * y];
+                let b1 = &permuted[(x + 1) % 5 + 5 * y];
+                let b2 = &permuted[(x + 2) % 5 + 5 * y];
+                state[x + 5 * y] = core::array::from_fn(|i| {
+                    let not_b1_and_b2 = and_not(builder, b1[i], b2[i]);
+                    xor(builder, b0[i], not_b1_and_b2)
+                });
+            }
+        }
+
+        // iota
+        for (i, bit) in state[0].iter_mut().enumerate() {
+            if (round_constant >> i) & 1 == 1 {
+                *bit = builder.not(*bit);
+            }
+        }
+    }
+}
+
+/// SHA-256 of a message whose length is fixed at circuit building time.
+pub fn sha256_circuit(builder: &mut Builder, message: &[Byte]) -> [Byte; DIGEST_BYTES] {
+    // 0x80, zeros, then the message length in bits as a big-endian u64
+    let mut padded = message.to_vec();
+    let num_zero_bytes =
+        (SHA256_BLOCK_BYTES - (message.len() + 1 + 8) % SHA256_BLOCK_BYTES) % SHA256_BLOCK_BYTES;
+    let padding = std::iter::once(0x80u8)
+        .chain(std::iter::repeat(0u8).take(num_zero_bytes))
+        .chain(((message.len() as u64) * 8).to_be_bytes())
+        .map(|byte| constant_byte(builder, byte))
+        .collect::<Vec<_>>();
+    padded.extend(padding);
+
+    let mut state = SHA256_INITIAL_STATE.map(|word| constant_word(builder, word));
+    for block in padded.chunks(SHA256_BLOCK_BYTES) {
+        let mut schedule = block.chunks(4).map(bytes_to_word_be).collect::<Vec<_>>();
+        for t in 16..64 {
+            let s0 = small_sigma(builder, &schedule[t - 15], 7, 18, 3);
+            let s1 = small_sigma(builder, &schedule[t - 2], 17, 19, 10);
+            let word = add_words(builder, &[schedule[t - 16], s0, schedule[t - 7], s1]);
+            schedule.push(word);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for t in 0..64 {
+            let big_sigma_1 = big_sigma(builder, &e, 6, 11, 25);
+            let ch: Word = core::array::from_fn(|i| choose(builder, e[i], f[i], g[i]));
+            let k = constant_word(builder, SHA256_ROUND_CONSTANTS[t]);
+            let temp1 = add_words(builder, &[h, big_sigma_1, ch, k, schedule[t]]);
+
+            let big_sigma_0 = big_sigma(builder, &a, 2, 13, 22);
+            let maj: Word = core::array::from_fn(|i| majority(builder, a[i], b[i], c[i]));
+            let temp2 = add_words(builder, &[big_sigma_0, maj]);
+
+            h = g;
+            g = f;
+            f = e;
+            e = add_words(builder, &[d, temp1]);
+            d = c;
+            c = b;
+            b = a;
+            a = add_words(builder, &[temp1, temp2]);
+        }
+
+        let working = [a, b, c, d, e, f, g, h];
+        state = core::array::from_fn(|i| add_words(builder, &[state[i], working[i]]));
+    }
+
+    // big-endian words
+    core::array::from_fn(|i| core::array::from_fn(|j| state[i / 4][(3 - i % 4) * 8 + j]))
+}
+
+/// Interprets a digest as a big-endian 256-bit integer.
+pub fn digest_to_biguint(builder: &mut Builder, digest: &[Byte; DIGEST_BYTES]) -> BigUintTarget {
+    BigUintTarget {
+        limbs: (0..DIGEST_BYTES / 4)
+            .map(|limb| {
+                let bits = (0..4)
+                    .rev()
+                    .flat_map(|byte| digest[DIGEST_BYTES - 4 * limb - 4 + byte])
+                    .collect::<Vec<_>>();
+                U32Target(builder.le_sum(bits.iter()))
+            })
+            .collect(),
+    }
+}
+
+pub fn constant_byte(builder: &mut Builder, byte: u8) -> Byte {
+    core::array::from_fn(|i| builder.constant_bool((byte >> i) & 1 == 1))
+}
+
+fn constant_word(builder: &mut Builder, word: u32) -> Word {
+    core::array::from_fn(|i| builder.constant_bool((word >> i) & 1 == 1))
+}
+
+fn bytes_to_lane(bytes: &[Byte]) -> Lane {
+    core::array::from_fn(|i| bytes[i / 8][i % 8])
+}
+
+fn bytes_to_word_be(bytes: &[Byte]) -> Word {
+    core::array::from_fn(|i| bytes[3 - i / 8][i % 8])
+}
+
+fn rotate_left(lane: &Lane, n: usize) -> Lane {
+    core::array::from_fn(|i| lane[(i + 64 - n) % 64])
+}
+
+fn rotate_right(word: &Word, n: usize) -> Word {
+    core::array::from_fn(|i| word[(i + n) % 32])
+}
+
+fn shift_right(builder: &mut Builder, word: &Word, n: usize) -> Word {
+    let zero = builder._false();
+    core::array::from_fn(|i| if i + n < 32 { word[i + n] } else { zero })
+}
+
+/// a ^ b = a + b - 2ab
+fn xor(builder: &mut Builder, a: BoolTarget, b: BoolTarget) -> BoolTarget {
+    let sum = builder.add(a.target, b.target);
+    BoolTarget::new_unsafe(builder.arithmetic(-F::TWO, F::ONE, a.target, b.target, sum))
+}
+
+fn xor_bits<const N: usize>(
+    builder: &mut Builder,
+    a: &[BoolTarget; N],
+    b: &[BoolTarget; N],
+) -> [BoolTarget; N] {
+    core::array::from_fn(|i| xor(builder, a[i], b[i]))
+}
+
+/// !a & b = b - ab
+fn and_not(builder: &mut Builder, a: BoolTarget, b: BoolTarget) -> BoolTarget {
+    BoolTarget::new_unsafe(builder.arithmetic(F::NEG_ONE, F::ONE, a.target, b.target, b.target))
+}
+
+/// (e & f) ^ (!e & g) = e * (f - g) + g
+fn choose(builder: &mut Builder, e: BoolTarget, f: BoolTarget, g: BoolTarget) -> BoolTarget {
+    let f_minus_g = builder.sub(f.target, g.target);
+    BoolTarget::new_unsafe(builder.arithmetic(F::ONE, F::ONE, e.target, f_minus_g, g.target))
+}
+
+/// (a & b) ^ (c & (a ^ b)), where the two terms are never both set
+fn majority(builder: &mut Builder, a: BoolTarget, b: BoolTarget, c: BoolTarget) -> BoolTarget {
+    let a_xor_b = xor(builder, a, b);
+    let c_and_a_xor_b = builder.mul(c.target, a_xor_b.target);
+    BoolTarget::new_unsafe(builder.arithmetic(F::ONE, F::ONE, a.target, b.target, c_and_a_xor_b))
+}
+
+fn big_sigma(builder: &mut Builder, word: &Word, r0: usize, r1: usize, r2: usize) -> Word {
+    let x = xor_bits(builder, &rotate_right(word, r0), &rotate_right(word, r1));
+    xor_bits(builder, &x, &rotate_right(word, r2))
+}
+
+fn small_sigma(builder: &mut Builder, word: &Word, r0: usize, r1: usize, s: usize) -> Word {
+    let x = xor_bits(builder, &rotate_right(word, r0), &rotate_right(word, r1));
+    let shifted = shift_right(builder, word, s);
+    xor_bits(builder, &x, &shifted)
+}
+
+/// Sum of up to 8 words modulo 2^32.
+fn add_words(builder: &mut Builder, words: &[Word]) -> Word {
+    let summands = words
+        .iter()
+        .map(|word| builder.le_sum(word.iter()))
+        .collect::<Vec<_>>();
+    let sum = builder.add_many(summands);
+    builder.split_le(sum, 32 + 3)[..32].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use circuit::types::config::{Builder, C, CIRCUIT_CONFIG};
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+    use sha2::{Digest, Sha256};
+    use sha3::Keccak256;
+
+    use super::{constant_byte, Byte, ByteHash, DIGEST_BYTES};
+
+    /// Message lengths around the padding boundaries: SHA-256 needs a second block from 56 bytes
+    /// on, Keccak-256 from 136 bytes on.
+    const MESSAGE_LENGTHS: [usize; 7] = [0, 55, 56, 64, 135, 136, 137];
+
+    /// Proves ``hash`` over a random message of ``len`` bytes against the expected digest.
+    fn check_hash_circuit(
+        rng: &mut StdRng,
+        hash: ByteHash,
+        len: usize,
+        expected: fn(&[u8]) -> [u8; DIGEST_BYTES],
+    ) {
+        let mut message = vec![0u8; len];
+        rng.fill_bytes(&mut message);
+
+        let mut builder = Builder::new(CircuitConfig {
+            num_wires: 136,
+            ..CIRCUIT_CONFIG
+        });
+        let message_target = (0..len)
+            .map(|_| core::array::from_fn(|_| builder.add_virtual_bool_target_safe()))
+            .collect::<Vec<Byte>>();
+        let digest = hash.hash_circuit(&mut builder, &message_target);
+        for (byte, expected_byte) in digest.iter().zip(expected(&message)) {
+            let expected_byte = constant_byte(&mut builder, expected_byte);
+            for (bit, expected_bit) in byte.iter().zip(expected_byte) {
+                builder.connect(bit.target, expected_bit.target);
+            }
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (byte, byte_target) in message.iter().zip(message_target.iter()) {
+            for (i, bit) in byte_target.iter().enumerate() {
+                pw.set_bool_target(*bit, (byte >> i) & 1 == 1);
+            }
+        }
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn keccak256_circuit_matches_sha3() {
+        let mut rng = StdRng::seed_from_u64(0x3);
+        for len in MESSAGE_LENGTHS {
+            check_hash_circuit(&mut rng, ByteHash::Keccak256, len, |message| {
+                Keccak256::digest(message).into()
+            });
+        }
+    }
+
+    #[test]
+    fn sha256_circuit_matches_sha2() {
+        let mut rng = StdRng::seed_from_u64(0x2);
+        for len in MESSAGE_LENGTHS {
+            check_hash_circuit(&mut rng, ByteHash::Sha256, len, |message| {
+                Sha256::digest(message).into()
+            });
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Native radix-2 FFT over the blob domain, converting between the evaluation form blobs are
+//! stored in (bit-reversed order, see [`BlobDomain`](crate::blob_domain::BlobDomain)) and
+//! coefficient form.
+
+use plonky2::field::types::Field;
+
+use crate::blob_domain::{blob_domain, reverse_bits};
+use crate::bls12_381_scalar_field::BLS12381Scalar;
+
+/// The coefficients, lowest degree first, of the polynomial whose evaluations over the domain of
+/// the same size are ``evaluations``, in bit-reversed order.
+pub fn evaluations_to_coefficients(evaluations: &[BLS12381Scalar]) -> Vec<BLS12381Scalar> {
+    let domain = blob_domain(evaluations.len());
+
+    let mut values = evaluations.to_vec();
+    bit_reverse_permutation(&mut values);
+    fft_in_place(&mut values, domain.generator().inverse());
+
+    let size_inv = domain.size_inv();
+    values.iter_mut().for_each(|value| *value *= size_inv);
+    values
+}
+
+/// Inverse of [`evaluations_to_coefficients`]: evaluates the polynomial over the domain of size
+/// ``coefficients.len()``, returning the evaluations in bit-reversed order.
+pub fn coefficients_to_evaluations(coefficients: &[BLS12381Scalar]) -> Vec<BLS12381Scalar> {
+    let domain = blob_domain(coefficients.len());
+
+    let mut values = coefficients.to_vec();
+    fft_in_place(&mut values, domain.generator());
+    bit_reverse_permutation(&mut values);
+    values
+}
+
+/// Reed-Solomon extension of ``evaluations`` to a domain ``extension_factor`` times larger.
+///
+/// Thanks to the bit-reversed order the first ``evaluations.len()`` entries of the result are
+/// ``evaluations`` themselves, the rest are the evaluations at the new points.
+pub fn extend_evaluations(
+    evaluations: &[BLS12381Scalar],
+    extension_factor: usize,
+) -> Vec<BLS12381Scalar> {
+    assert!(extension_factor.is_power_of_two());
+
+    let mut coefficients = evaluations_to_coefficients(evaluations);
+    coefficients.resize(evaluations.len() * extension_factor, BLS12381Scalar::ZERO);
+    coefficients_to_evaluations(&coefficients)
+}
+
+/// The degree of the polynomial with the given coefficients, `None` for the zero polynomial.
+pub fn degree(coefficients: &[BLS12381Scalar]) -> Option<usize> {
+    coefficients
+        .iter()
+        .rposition(|coefficient| *coefficient != BLS12381Scalar::ZERO)
+}
+
+/// Reorders ``values`` so that ``values[i]`` moves to index `brp(i)`, see [`reverse_bits`]. The
+/// permutation is its own inverse.
+pub fn bit_reverse_permutation<T>(values: &mut [T]) {
+    assert!(values.len().is_power_of_two());
+    let bits = values.len().trailing_zeros() as usize;
+
+    for i in 0..values.len() {
+        let j = reverse_bits(i, bits);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+/// Iterative Cooley-Tukey FFT. Maps ``values[j]`` to ``sum_k values[k] * root^(j * k)`` in
+/// natural order, where ``root`` is a primitive ``values.len()``'th root of unity.
+fn fft_in_place(values: &mut [BLS12381Scalar], root: BLS12381Scalar) {
+    let n = values.len();
+    bit_reverse_permutation(values);
+
+    let mut half = 1;
+    while half < n {
+        // primitive (2 * half)'th root of unity
+        let stage_root = root.exp_u64((n / (2 * half)) as u64);
+        let twiddles = stage_root.powers().take(half).collect::<Vec<_>>();
+
+        for chunk in values.chunks_mut(2 * half) {
+            let (low, high) = chunk.split_at_mut(half);
+            for ((u, v), twiddle) in low.iter_mut().zip(high.iter_mut()).zip(twiddles.iter()) {
+                let t = *v * *twiddle;
+                *v = *u - t;
+                *u += t;
+            }
+        }
+        half *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::{Field, Sample};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{
+        coefficients_to_evaluations, degree, evaluations_to_coefficients, extend_evaluations,
+    };
+    use crate::blob_domain::blob_domain;
+    use crate::blob_polynomial::BLOB_WIDTH;
+    use crate::bls12_381_scalar_field::BLS12381Scalar;
+
+    fn random_scalars(rng: &mut StdRng, n: usize) -> Vec<BLS12381Scalar> {
+        (0..n).map(|_| BLS12381Scalar::sample(rng)).collect()
+    }
+
+    /// Evaluates the polynomial with the given coefficients at ``x`` with Horner's rule.
+    fn evaluate(coefficients: &[BLS12381Scalar], x: BLS12381Scalar) -> BLS12381Scalar {
+        coefficients
+            .iter()
+            .rev()
+            .fold(BLS12381Scalar::ZERO, |acc, coefficient| {
+                acc * x + *coefficient
+            })
+    }
+
+    #[test]
+    fn fft_ifft_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0xff7);
+        for size in [1, 2, 16, BLOB_WIDTH] {
+            let evaluations = random_scalars(&mut rng, size);
+            let coefficients = evaluations_to_coefficients(&evaluations);
+            assert_eq!(coefficients_to_evaluations(&coefficients), evaluations);
+
+            let coefficients = random_scalars(&mut rng, size);
+            let evaluations = coefficients_to_evaluations(&coefficients);
+            assert_eq!(evaluations_to_coefficients(&evaluations), coefficients);
+        }
+    }
+
+    #[test]
+    fn evaluations_are_in_bit_reversed_order() {
+        let mut rng = StdRng::seed_from_u64(0xff7);
+        let coefficients = random_scalars(&mut rng, 16);
+        let evaluations = coefficients_to_evaluations(&coefficients);
+
+        let domain = blob_domain(16);
+        for (i, evaluation) in evaluations.iter().enumerate() {
+            assert_eq!(*evaluation, evaluate(&coefficients, domain.root(i)));
+        }
+    }
+
+    #[test]
+    fn extend_evaluations_keeps_original_evaluations() {
+        let mut rng = StdRng::seed_from_u64(0xff7);
+        for (size, extension_factor) in [(16, 4), (BLOB_WIDTH, 2)] {
+            let evaluations = random_scalars(&mut rng, size);
+            let extended = extend_evaluations(&evaluations, extension_factor);
+            assert_eq!(extended.len(), size * extension_factor);
+            assert_eq!(extended[..size], evaluations[..]);
+
+            // The new points are evaluations of the same polynomial over the larger domain.
+            let coefficients = evaluations_to_coefficients(&evaluations);
+            let domain = blob_domain(size * extension_factor);
+            for i in (size..extended.len()).step_by(size / 8) {
+                assert_eq!(extended[i], evaluate(&coefficients, domain.root(i)));
+            }
+        }
+    }
+
+    #[test]
+    fn degree_of_known_polynomials() {
+        let mut rng = StdRng::seed_from_u64(0xff7);
+        assert_eq!(degree(&[BLS12381Scalar::ZERO; 16]), None);
+        assert_eq!(
+            degree(&evaluations_to_coefficients(&[BLS12381Scalar::TWO; 16])),
+            Some(0)
+        );
+
+        for d in [1, 7, 15, BLOB_WIDTH / 2, BLOB_WIDTH - 1] {
+            let mut coefficients = random_scalars(&mut rng, d + 1);
+            // The leading coefficient must not vanish for the degree to be exactly `d`.
+            coefficients[d] = BLS12381Scalar::ONE;
+            let size = (d + 1).next_power_of_two().max(16);
+            coefficients.resize(size, BLS12381Scalar::ZERO);
+
+            let evaluations = coefficients_to_evaluations(&coefficients);
+            assert_eq!(degree(&evaluations_to_coefficients(&evaluations)), Some(d));
+        }
+    }
+}
@@ -1,33 +1,596 @@
-use circuit::bigint::biguint::BigUintTarget;
+use circuit::bigint::biguint::{BigUintTarget, CircuitBuilderBiguint};
 use circuit::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
 use circuit::types::config::{Builder, F};
 use circuit::u32::gadgets::arithmetic_u32::CircuitBuilderU32;
-use plonky2::hash::hash_types::HashOutTarget;
-use plonky2::plonk::config::AlgebraicHasher;
+use num::BigUint;
+use plonky2::field::types::{Field, Field64, PrimeField64};
+use plonky2::hash::hash_types::{HashOut, HashOutTarget};
+use plonky2::hash::hashing::hash_n_to_m_no_pad;
+use plonky2::iop::target::Target;
+use plonky2::plonk::config::{AlgebraicHasher, Hasher};
 
 use crate::bls12_381_scalar_field::BLS12381Scalar;
+use crate::byte_hashes::DIGEST_BYTES;
+use crate::kzg::KZG_COMMITMENT_LIMBS;
 
-pub fn fiat_shamir_for_proof_of_commitment_equivalence<H: AlgebraicHasher<F>>(
+/// Number of Goldilocks elements squeezed per challenge. Eight elements make a ~512-bit integer,
+/// so reducing it modulo the ~255-bit BLS12-381 scalar order leaves a bias of about 2^-257.
+pub const CHALLENGE_SQUEEZE_ELEMENTS: usize = 8;
+
+/// Protocol tag (and version) the equivalence transcript starts with.
+pub const PROTOCOL_LABEL: &[u8] = b"poe/eip-4844/commitment-equivalence/v1";
+
+/// Bytes packed into one Goldilocks element when absorbing a label.
+const LABEL_BYTES_PER_ELEMENT: usize = 7;
+
+/// Challenge of the commitment equivalence protocol for a single blob, in a fresh transcript.
+pub fn fiat_shamir_for_proof_of_commitment_equivalence<
+    H: AlgebraicHasher<F>,
+    C: CircuitCommitmentTarget,
+>(
     builder: &mut Builder,
-    circuit_commitment: &HashOutTarget,
+    circuit_commitment: &C,
     kzg_commitment: &BigUintTarget,
 ) -> NonNativeTarget<BLS12381Scalar> {
-    let challenge_point_biguint = BigUintTarget {
-        limbs: builder
-            .hash_n_to_hash_no_pad::<H>(
-                circuit_commitment
-                    .elements
-                    .iter()
-                    .chain(kzg_commitment.limbs.iter().map(|u32_elem| &u32_elem.0))
-                    .map(|reference| *reference)
-                    .collect(),
-            )
-            .elements
-            .map(|elem| builder.split_u64_to_u32s_le(elem))
-            .iter()
-            .flat_map(|e| *e)
-            .collect::<Vec<_>>(),
+    let mut transcript = TranscriptTarget::new(builder);
+    fiat_shamir_with_transcript::<H, C>(
+        builder,
+        &mut transcript,
+        circuit_commitment,
+        kzg_commitment,
+    )
+}
+
+/// Native counterpart of [`fiat_shamir_for_proof_of_commitment_equivalence`], taking the circuit
+/// commitment (see `blob_commitment_native` and `blob_commitment_bytes_native`) and the compressed
+/// KZG commitment as an integer.
+pub fn challenge_point_native<H: AlgebraicHasher<F>, C: CircuitCommitment>(
+    circuit_commitment: &C,
+    kzg_commitment: &BigUint,
+) -> BLS12381Scalar {
+    fiat_shamir_with_transcript_native::<H, C>(
+        &mut Transcript::new(),
+        circuit_commitment,
+        kzg_commitment,
+        KZG_COMMITMENT_LIMBS,
+    )
+}
+
+/// Challenge of the commitment equivalence protocol for a single blob, in a transcript that may
+/// already be bound to additional context such as chain id, rollup id and batch number.
+pub fn fiat_shamir_with_transcript<H: AlgebraicHasher<F>, C: CircuitCommitmentTarget>(
+    builder: &mut Builder,
+    transcript: &mut TranscriptTarget,
+    circuit_commitment: &C,
+    kzg_commitment: &BigUintTarget,
+) -> NonNativeTarget<BLS12381Scalar> {
+    fiat_shamir_multi_blob_with_transcript::<H, C>(
+        builder,
+        transcript,
+        std::slice::from_ref(circuit_commitment),
+        std::slice::from_ref(kzg_commitment),
+    )
+}
+
+/// Native counterpart of [`fiat_shamir_with_transcript`]. ``kzg_commitment`` is absorbed with
+/// ``kzg_commitment_limbs`` u32 limbs, the size of the target it mirrors.
+pub fn fiat_shamir_with_transcript_native<H: AlgebraicHasher<F>, C: CircuitCommitment>(
+    transcript: &mut Transcript,
+    circuit_commitment: &C,
+    kzg_commitment: &BigUint,
+    kzg_commitment_limbs: usize,
+) -> BLS12381Scalar {
+    fiat_shamir_multi_blob_with_transcript_native::<H, C>(
+        transcript,
+        std::slice::from_ref(circuit_commitment),
+        std::slice::from_ref(kzg_commitment),
+        kzg_commitment_limbs,
+    )
+}
+
+/// One challenge for several blobs, e.g. all blobs of a blob-carrying transaction, in a fresh
+/// transcript. With a single blob this is [`fiat_shamir_for_proof_of_commitment_equivalence`].
+pub fn fiat_shamir_for_multi_blob_equivalence<H: AlgebraicHasher<F>, C: CircuitCommitmentTarget>(
+    builder: &mut Builder,
+    circuit_commitments: &[C],
+    kzg_commitments: &[BigUintTarget],
+) -> NonNativeTarget<BLS12381Scalar> {
+    let mut transcript = TranscriptTarget::new(builder);
+    fiat_shamir_multi_blob_with_transcript::<H, C>(
+        builder,
+        &mut transcript,
+        circuit_commitments,
+        kzg_commitments,
+    )
+}
+
+/// Native counterpart of [`fiat_shamir_for_multi_blob_equivalence`].
+pub fn challenge_point_multi_blob_native<H: AlgebraicHasher<F>, C: CircuitCommitment>(
+    circuit_commitments: &[C],
+    kzg_commitments: &[BigUint],
+) -> BLS12381Scalar {
+    fiat_shamir_multi_blob_with_transcript_native::<H, C>(
+        &mut Transcript::new(),
+        circuit_commitments,
+        kzg_commitments,
+        KZG_COMMITMENT_LIMBS,
+    )
+}
+
+/// Absorbs the number of blobs, then the circuit and KZG commitment of every blob in order, and
+/// squeezes the challenge shared by all of them.
+pub fn fiat_shamir_multi_blob_with_transcript<H: AlgebraicHasher<F>, C: CircuitCommitmentTarget>(
+    builder: &mut Builder,
+    transcript: &mut TranscriptTarget,
+    circuit_commitments: &[C],
+    kzg_commitments: &[BigUintTarget],
+) -> NonNativeTarget<BLS12381Scalar> {
+    assert!(!circuit_commitments.is_empty());
+    assert_eq!(circuit_commitments.len(), kzg_commitments.len());
+
+    transcript.absorb_label(builder, b"blob_count");
+    let blob_count = builder.constant(F::from_canonical_usize(circuit_commitments.len()));
+    transcript.absorb_elements(&[blob_count]);
+    for (circuit_commitment, kzg_commitment) in circuit_commitments.iter().zip(kzg_commitments) {
+        circuit_commitment.absorb_into(builder, transcript);
+        transcript.absorb_label(builder, b"kzg_commitment");
+        transcript.absorb_biguint(builder, kzg_commitment);
+    }
+    transcript.squeeze_scalar::<H>(builder)
+}
+
+/// Native counterpart of [`fiat_shamir_multi_blob_with_transcript`].
+pub fn fiat_shamir_multi_blob_with_transcript_native<
+    H: AlgebraicHasher<F>,
+    C: CircuitCommitment,
+>(
+    transcript: &mut Transcript,
+    circuit_commitments: &[C],
+    kzg_commitments: &[BigUint],
+    kzg_commitment_limbs: usize,
+) -> BLS12381Scalar {
+    assert!(!circuit_commitments.is_empty());
+    assert_eq!(circuit_commitments.len(), kzg_commitments.len());
+
+    transcript.absorb_label(b"blob_count");
+    transcript.absorb_elements(&[F::from_canonical_usize(circuit_commitments.len())]);
+    for (circuit_commitment, kzg_commitment) in circuit_commitments.iter().zip(kzg_commitments) {
+        circuit_commitment.absorb_into(transcript);
+        transcript.absorb_label(b"kzg_commitment");
+        transcript.absorb_biguint(kzg_commitment, kzg_commitment_limbs);
+    }
+    transcript.squeeze_scalar::<H>()
+}
+
+/// ``num_points`` challenges for evaluating the same blobs at several points. The first is the
+/// challenge of [`fiat_shamir_multi_blob_with_transcript`]; every further one is squeezed from the
+/// transcript state left by the previous squeeze.
+pub fn fiat_shamir_multi_point_with_transcript<
+    H: AlgebraicHasher<F>,
+    C: CircuitCommitmentTarget,
+>(
+    builder: &mut Builder,
+    transcript: &mut TranscriptTarget,
+    circuit_commitments: &[C],
+    kzg_commitments: &[BigUintTarget],
+    num_points: usize,
+) -> Vec<NonNativeTarget<BLS12381Scalar>> {
+    assert!(num_points > 0);
+
+    let mut challenges = vec![fiat_shamir_multi_blob_with_transcript::<H, C>(
+        builder,
+        transcript,
+        circuit_commitments,
+        kzg_commitments,
+    )];
+    for _ in 1..num_points {
+        challenges.push(transcript.squeeze_scalar::<H>(builder));
+    }
+    challenges
+}
+
+/// Native counterpart of [`fiat_shamir_multi_point_with_transcript`].
+pub fn fiat_shamir_multi_point_with_transcript_native<
+    H: AlgebraicHasher<F>,
+    C: CircuitCommitment,
+>(
+    transcript: &mut Transcript,
+    circuit_commitments: &[C],
+    kzg_commitments: &[BigUint],
+    kzg_commitment_limbs: usize,
+    num_points: usize,
+) -> Vec<BLS12381Scalar> {
+    assert!(num_points > 0);
+
+    let mut challenges = vec![fiat_shamir_multi_blob_with_transcript_native::<H, C>(
+        transcript,
+        circuit_commitments,
+        kzg_commitments,
+        kzg_commitment_limbs,
+    )];
+    for _ in 1..num_points {
+        challenges.push(transcript.squeeze_scalar::<H>());
+    }
+    challenges
+}
+
+/// Native challenges of a [`fiat_shamir_multi_point_with_transcript`] run in a fresh transcript.
+pub fn challenge_points_native<H: AlgebraicHasher<F>, C: CircuitCommitment>(
+    circuit_commitments: &[C],
+    kzg_commitments: &[BigUint],
+    num_points: usize,
+) -> Vec<BLS12381Scalar> {
+    fiat_shamir_multi_point_with_transcript_native::<H, C>(
+        &mut Transcript::new(),
+        circuit_commitments,
+        kzg_commitments,
+        KZG_COMMITMENT_LIMBS,
+        num_points,
+    )
+}
+
+/// A circuit-side blob commitment, absorbed under a label specific to its kind so that a Poseidon
+/// hash and a byte-hash digest can never be confused.
+pub trait CircuitCommitmentTarget {
+    fn absorb_into(&self, builder: &mut Builder, transcript: &mut TranscriptTarget);
+}
+
+/// [`BlobPolynomial::commit`](crate::blob_polynomial::BlobPolynomial::commit) and
+/// [`BlobPolynomial::commit_packed`](crate::blob_polynomial::BlobPolynomial::commit_packed).
+impl CircuitCommitmentTarget for HashOutTarget {
+    fn absorb_into(&self, builder: &mut Builder, transcript: &mut TranscriptTarget) {
+        transcript.absorb_label(builder, b"circuit_commitment");
+        transcript.absorb_hash(self);
+    }
+}
+
+/// [`BlobPolynomial::commit_bytes`](crate::blob_polynomial::BlobPolynomial::commit_bytes).
+impl CircuitCommitmentTarget for BigUintTarget {
+    fn absorb_into(&self, builder: &mut Builder, transcript: &mut TranscriptTarget) {
+        transcript.absorb_label(builder, b"circuit_commitment_digest");
+        transcript.absorb_biguint(builder, self);
+    }
+}
+
+/// Native counterpart of [`CircuitCommitmentTarget`].
+pub trait CircuitCommitment {
+    fn absorb_into(&self, transcript: &mut Transcript);
+}
+
+impl CircuitCommitment for HashOut<F> {
+    fn absorb_into(&self, transcript: &mut Transcript) {
+        transcript.absorb_label(b"circuit_commitment");
+        transcript.absorb_hash(self);
+    }
+}
+
+impl CircuitCommitment for [u8; DIGEST_BYTES] {
+    fn absorb_into(&self, transcript: &mut Transcript) {
+        transcript.absorb_label(b"circuit_commitment_digest");
+        transcript.absorb_biguint(&BigUint::from_bytes_be(self), DIGEST_BYTES / 4);
+    }
+}
+
+/// In-circuit Fiat-Shamir transcript. Produces the same challenges as [`Transcript`] when fed the
+/// same values in the same order.
+///
+/// Absorbed elements are buffered until a squeeze, which hashes everything absorbed so far. The
+/// squeezed elements then replace the buffer, so later challenges depend on the whole history.
+pub struct TranscriptTarget {
+    buffer: Vec<Target>,
+}
+
+impl TranscriptTarget {
+    /// Starts a transcript tagged with [`PROTOCOL_LABEL`].
+    pub fn new(builder: &mut Builder) -> Self {
+        let mut transcript = Self { buffer: Vec::new() };
+        transcript.absorb_label(builder, PROTOCOL_LABEL);
+        transcript
+    }
+
+    pub fn absorb_elements(&mut self, elements: &[Target]) {
+        self.buffer.extend_from_slice(elements);
+    }
+
+    /// Absorbs a length-prefixed constant label for domain separation.
+    pub fn absorb_label(&mut self, builder: &mut Builder, label: &[u8]) {
+        let elements = label_to_elements(label)
+            .into_iter()
+            .map(|element| builder.constant(element))
+            .collect::<Vec<_>>();
+        self.absorb_elements(&elements);
+    }
+
+    pub fn absorb_hash(&mut self, hash: &HashOutTarget) {
+        self.absorb_elements(&hash.elements);
+    }
+
+    /// Absorbs an integer such as a chain id, rollup id or batch number as a single element, see
+    /// [`Transcript::absorb_u64`].
+    pub fn absorb_u64(&mut self, value: Target) {
+        self.absorb_elements(&[value]);
+    }
+
+    /// Absorbs the number of limbs followed by the u32 limbs, least significant first.
+    pub fn absorb_biguint(&mut self, builder: &mut Builder, value: &BigUintTarget) {
+        let num_limbs = builder.constant(F::from_canonical_usize(value.limbs.len()));
+        self.absorb_elements(&[num_limbs]);
+        self.buffer
+            .extend(value.limbs.iter().map(|u32_target| u32_target.0));
+    }
+
+    pub fn squeeze_scalar<H: AlgebraicHasher<F>>(
+        &mut self,
+        builder: &mut Builder,
+    ) -> NonNativeTarget<BLS12381Scalar> {
+        let elements = builder
+            .hash_n_to_m_no_pad::<H>(std::mem::take(&mut self.buffer), CHALLENGE_SQUEEZE_ELEMENTS);
+        self.buffer = elements.clone();
+        elements_to_scalar(builder, &elements)
+    }
+}
+
+/// Native Fiat-Shamir transcript, see [`TranscriptTarget`].
+pub struct Transcript {
+    buffer: Vec<F>,
+}
+
+impl Transcript {
+    /// Starts a transcript tagged with [`PROTOCOL_LABEL`].
+    pub fn new() -> Self {
+        let mut transcript = Self { buffer: Vec::new() };
+        transcript.absorb_label(PROTOCOL_LABEL);
+        transcript
+    }
+
+    pub fn absorb_elements(&mut self, elements: &[F]) {
+        self.buffer.extend_from_slice(elements);
+    }
+
+    pub fn absorb_label(&mut self, label: &[u8]) {
+        self.absorb_elements(&label_to_elements(label));
+    }
+
+    pub fn absorb_hash(&mut self, hash: &HashOut<F>) {
+        self.absorb_elements(&hash.elements);
+    }
+
+    /// Absorbs an integer such as a chain id, rollup id or batch number as a single element.
+    /// ``value`` must be below the Goldilocks order, which leaves room for any realistic id.
+    pub fn absorb_u64(&mut self, value: u64) {
+        assert!(
+            value < F::ORDER,
+            "{} does not fit in a Goldilocks element",
+            value
+        );
+        self.absorb_elements(&[F::from_canonical_u64(value)]);
+    }
+
+    /// Absorbs ``value`` as ``num_limbs`` u32 limbs, mirroring [`TranscriptTarget::absorb_biguint`]
+    /// on a target of that size.
+    pub fn absorb_biguint(&mut self, value: &BigUint, num_limbs: usize) {
+        let mut limbs = value.to_u32_digits();
+        assert!(
+            limbs.len() <= num_limbs,
+            "value does not fit in {} limbs",
+            num_limbs
+        );
+        limbs.resize(num_limbs, 0);
+
+        self.absorb_elements(&[F::from_canonical_usize(num_limbs)]);
+        self.buffer
+            .extend(limbs.into_iter().map(F::from_canonical_u32));
+    }
+
+    pub fn squeeze_scalar<H: AlgebraicHasher<F>>(&mut self) -> BLS12381Scalar {
+        let elements = hash_n_to_m_no_pad::<F, <H as Hasher<F>>::Permutation>(
+            &std::mem::take(&mut self.buffer),
+            CHALLENGE_SQUEEZE_ELEMENTS,
+        );
+        self.buffer = elements.clone();
+        elements_to_scalar_native(&elements)
+    }
+}
+
+impl Default for Transcript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The label length, followed by its bytes packed little-endian into 7-byte elements.
+fn label_to_elements(label: &[u8]) -> Vec<F> {
+    std::iter::once(F::from_canonical_usize(label.len()))
+        .chain(label.chunks(LABEL_BYTES_PER_ELEMENT).map(|chunk| {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            F::from_canonical_u64(u64::from_le_bytes(bytes))
+        }))
+        .collect()
+}
+
+/// Combines the squeezed ``e_0, ..., e_7`` into the integer
+///    sum_(i<8)  e_i * p^i
+/// where ``p`` is the Goldilocks order. This is uniform in ``[0, p^8)``, and its reduction
+/// modulo the scalar order is the challenge.
+fn elements_to_scalar(
+    builder: &mut Builder,
+    elements: &[Target],
+) -> NonNativeTarget<BLS12381Scalar> {
+    let goldilocks_order = builder.constant_biguint(&F::order());
+
+    // Horner's rule, starting from the most significant element
+    let mut wide = goldilocks_to_biguint(builder, elements[elements.len() - 1]);
+    for element in elements.iter().rev().skip(1) {
+        let element_big = goldilocks_to_biguint(builder, *element);
+        let shifted = builder.mul_biguint(&wide, &goldilocks_order);
+        wide = builder.add_biguint(&shifted, &element_big);
+    }
+
+    let modulus = builder.constant_biguint(&BLS12381Scalar::order());
+    let reduced = builder.rem_biguint(&wide, &modulus);
+    builder.biguint_to_nonnative(&reduced)
+}
+
+/// Native counterpart of [`elements_to_scalar`].
+fn elements_to_scalar_native(elements: &[F]) -> BLS12381Scalar {
+    let wide = elements
+        .iter()
+        .rev()
+        .fold(BigUint::from(0u64), |acc, element| {
+            acc * F::order() + BigUint::from(element.to_canonical_u64())
+        });
+
+    BLS12381Scalar::from_noncanonical_biguint(wide)
+}
+
+/// Splits a Goldilocks element into two u32 limbs, rejecting the non-canonical split of
+/// ``x + p`` that would otherwise be available for ``x < 2^32 - 1``.
+fn goldilocks_to_biguint(builder: &mut Builder, element: Target) -> BigUintTarget {
+    let [low, high] = builder.split_u64_to_u32s_le(element);
+
+    // high == 2^32 - 1  =>  low == 0
+    let u32_max = builder.constant(F::from_canonical_u32(u32::MAX));
+    let high_is_max = builder.is_equal(high.0, u32_max);
+    let low_if_high_is_max = builder.mul(high_is_max.target, low.0);
+    builder.assert_zero(low_if_high_is_max);
+
+    BigUintTarget {
+        limbs: vec![low, high],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use circuit::bigint::biguint::{CircuitBuilderBiguint, WitnessBigUint};
+    use circuit::poseidon2::hash::Poseidon2Hash;
+    use circuit::types::config::{Builder, C, CIRCUIT_CONFIG, F};
+    use num::{BigUint, ToPrimitive};
+    use plonky2::field::types::{Field, PrimeField, Sample};
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{
+        elements_to_scalar_native, Transcript, TranscriptTarget, CHALLENGE_SQUEEZE_ELEMENTS,
     };
+    use crate::bls12_381_scalar_field::BLS12381Scalar;
+
+    const NUM_SAMPLES: usize = 16 * 1024;
+    const NUM_BUCKETS: usize = 16;
+    /// The 99.9th percentile of the chi-square distribution with `NUM_BUCKETS - 1` degrees of
+    /// freedom.
+    const CHI_SQUARE_THRESHOLD: f64 = 37.70;
+
+    /// Pearson's chi-square statistic of ``scalars`` against the uniform distribution on
+    /// `[0, r)`, with the range split into [`NUM_BUCKETS`] equal intervals.
+    fn chi_square(scalars: impl Iterator<Item = BLS12381Scalar>) -> f64 {
+        let order = BLS12381Scalar::order();
+        let mut counts = [0usize; NUM_BUCKETS];
+        for scalar in scalars {
+            let bucket = scalar.to_canonical_biguint() * NUM_BUCKETS / &order;
+            counts[bucket.to_usize().unwrap()] += 1;
+        }
+
+        let expected = NUM_SAMPLES as f64 / NUM_BUCKETS as f64;
+        counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    #[test]
+    fn elements_to_scalar_is_horner_in_goldilocks_order() {
+        let elements = [F::NEG_ONE; CHALLENGE_SQUEEZE_ELEMENTS];
+        let wide = F::order().pow(CHALLENGE_SQUEEZE_ELEMENTS as u32) - 1u32;
+        assert_eq!(
+            elements_to_scalar_native(&elements),
+            BLS12381Scalar::from_noncanonical_biguint(wide)
+        );
+
+        let mut elements = [F::ZERO; CHALLENGE_SQUEEZE_ELEMENTS];
+        elements[1] = F::ONE;
+        assert_eq!(
+            elements_to_scalar_native(&elements).to_canonical_biguint(),
+            F::order()
+        );
+    }
+
+    #[test]
+    fn elements_to_scalar_is_uniform() {
+        let mut rng = StdRng::seed_from_u64(0xf5);
+        let statistic = chi_square((0..NUM_SAMPLES).map(|_| {
+            let elements = (0..CHALLENGE_SQUEEZE_ELEMENTS)
+                .map(|_| F::sample(&mut rng))
+                .collect::<Vec<_>>();
+            elements_to_scalar_native(&elements)
+        }));
+        assert!(statistic < CHI_SQUARE_THRESHOLD, "chi-square {statistic}");
+    }
+
+    #[test]
+    fn squeeze_scalar_is_uniform() {
+        let mut transcript = Transcript::new();
+        transcript.absorb_biguint(&BigUint::from(0xf5u32), 1);
+        let statistic =
+            chi_square((0..NUM_SAMPLES).map(|_| transcript.squeeze_scalar::<Poseidon2Hash>()));
+        assert!(statistic < CHI_SQUARE_THRESHOLD, "chi-square {statistic}");
+    }
+
+    #[test]
+    fn transcript_target_matches_native() {
+        let mut rng = StdRng::seed_from_u64(0x13);
+        let hash = HashOut::<F>::sample(&mut rng);
+        // the high limb is all ones
+        let digest = BigUint::from_bytes_be(&[0xff; 32]) - 1u32;
+        let small = BigUint::from(0xf5u32);
+        let batch_number = 0x0123_4567_89ab_cdef;
+
+        let mut transcript = Transcript::new();
+        transcript.absorb_label(b"test");
+        transcript.absorb_hash(&hash);
+        transcript.absorb_biguint(&digest, 8);
+        transcript.absorb_biguint(&small, 2);
+        transcript.absorb_u64(batch_number);
+        let challenges = [
+            transcript.squeeze_scalar::<Poseidon2Hash>(),
+            transcript.squeeze_scalar::<Poseidon2Hash>(),
+        ];
+        assert_ne!(challenges[0], challenges[1]);
+
+        let mut builder = Builder::new(CIRCUIT_CONFIG);
+        let hash_target = builder.add_virtual_hash();
+        let digest_target = builder.add_virtual_biguint_target(8);
+        let small_target = builder.add_virtual_biguint_target(2);
+        let batch_number_target = builder.add_virtual_target();
+
+        let mut transcript_target = TranscriptTarget::new(&mut builder);
+        transcript_target.absorb_label(&mut builder, b"test");
+        transcript_target.absorb_hash(&hash_target);
+        transcript_target.absorb_biguint(&mut builder, &digest_target);
+        transcript_target.absorb_biguint(&mut builder, &small_target);
+        transcript_target.absorb_u64(batch_number_target);
+        for challenge in challenges {
+            let challenge_target = transcript_target.squeeze_scalar::<Poseidon2Hash>(&mut builder);
+            let expected = BLS12381Scalar::constant(&mut builder, challenge);
+            builder.connect_biguint(&challenge_target.value, &expected.value);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_hash_target(hash_target, hash);
+        pw.set_biguint_target(&digest_target, &digest);
+        pw.set_biguint_target(&small_target, &small);
+        pw.set_target(batch_number_target, F::from_canonical_u64(batch_number));
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
 
-    builder.biguint_to_nonnative(&challenge_point_biguint)
+    #[test]
+    #[should_panic(expected = "does not fit in a Goldilocks element")]
+    fn absorb_u64_rejects_values_above_the_goldilocks_order() {
+        Transcript::new().absorb_u64(u64::MAX);
+    }
 }
@@ -1,14 +1,21 @@
-use std::fs;
 use std::io::Read;
 use std::path::Path;
+use std::{fmt, fs};
 
 use itertools::Itertools;
 use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
 
 use crate::blob_polynomial::BLOB_WIDTH;
+use crate::kzg::{kzg_to_versioned_hash, BYTES_PER_FIELD_ELEMENT, BYTES_PER_G1};
 
 pub const DIR_PATH: &str = "../files";
 
+/// Version of the proof bundle format, see [`ProofBundle`].
+pub const PROOF_BUNDLE_VERSION: u32 = 1;
+
+const PROOF_BUNDLE_MAGIC: &[u8; 4] = b"POEB";
+
 pub fn read_blob() -> [BigUint; BLOB_WIDTH] {
     let mut file = fs::File::open(Path::new(DIR_PATH).join("blob")).unwrap();
     let mut blob_hex_string = String::new();
@@ -44,3 +51,408 @@ pub fn read_bls1_381_scalar(path: &str) -> BigUint {
 
     BigUint::from_str_radix(&bls1_381_scalar_hex_string, 16).unwrap()
 }
+
+pub fn read_kzg_commitment() -> [u8; BYTES_PER_G1] {
+    let mut file = fs::File::open(Path::new(DIR_PATH).join("commitment")).unwrap();
+    let mut kzg_commitment_hex_string = String::new();
+    file.read_to_string(&mut kzg_commitment_hex_string).unwrap();
+    assert_eq!(kzg_commitment_hex_string.len(), BYTES_PER_G1 * 2);
+
+    hex::decode(kzg_commitment_hex_string.trim())
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+pub fn read_versioned_hash() -> [u8; 32] {
+    let mut file = fs::File::open(Path::new(DIR_PATH).join("hash")).unwrap();
+    let mut versioned_hash_hex_string = String::new();
+    file.read_to_string(&mut versioned_hash_hex_string).unwrap();
+    assert_eq!(versioned_hash_hex_string.len(), 64);
+
+    hex::decode(versioned_hash_hex_string.trim())
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+/// Reads the versioned hash and asserts that it belongs to the KZG commitment next to it, so that
+/// a mismatched artifact set is caught before proving rather than by the on-chain verifier.
+pub fn read_and_check_versioned_hash() -> [u8; 32] {
+    let versioned_hash = read_versioned_hash();
+    assert_eq!(
+        versioned_hash,
+        kzg_to_versioned_hash(&read_kzg_commitment()),
+        "versioned hash does not match the KZG commitment"
+    );
+    versioned_hash
+}
+
+/// Reads the KZG opening proof saved next to the commitment.
+pub fn read_kzg_proof() -> [u8; BYTES_PER_G1] {
+    let mut file = fs::File::open(Path::new(DIR_PATH).join("proof")).unwrap();
+    let mut kzg_proof_hex_string = String::new();
+    file.read_to_string(&mut kzg_proof_hex_string).unwrap();
+    assert_eq!(kzg_proof_hex_string.len(), BYTES_PER_G1 * 2);
+
+    hex::decode(kzg_proof_hex_string.trim())
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+/// All artifacts of a poe proof in a single file: what the on-chain verifier needs (versioned
+/// hash, commitment, `x`, `y` and the KZG opening proof) together with the plonky2 proof and the
+/// digest of the circuit it was produced for.
+///
+/// Bundles are written either as JSON, with every byte string hex encoded, or in a compact binary
+/// form:
+///
+/// ```text
+/// "POEB" | version (u32 LE) | versioned_hash (32) | kzg_commitment (48) | x (32) | y (32)
+///        | kzg_proof (48) | circuit_digest (32) | proof length (u32 LE) | proof
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofBundle {
+    pub versioned_hash: [u8; 32],
+    pub kzg_commitment: [u8; BYTES_PER_G1],
+    /// The challenge point, big-endian.
+    pub x: [u8; BYTES_PER_FIELD_ELEMENT],
+    /// The blob evaluation at `x`, big-endian.
+    pub y: [u8; BYTES_PER_FIELD_ELEMENT],
+    /// KZG opening proof of the commitment at `(x, y)`.
+    pub kzg_proof: [u8; BYTES_PER_G1],
+    /// The serialized plonky2 `ProofWithPublicInputs`.
+    pub proof: Vec<u8>,
+    pub circuit_digest: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProofBundleJson {
+    version: u32,
+    versioned_hash: String,
+    kzg_commitment: String,
+    x: String,
+    y: String,
+    kzg_proof: String,
+    proof: String,
+    circuit_digest: String,
+}
+
+/// Why a [`ProofBundle`] could not be built, or read back from its serialized form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofBundleError {
+    /// Bundles hold a single blob evaluated at a single point, see `PoeCircuit::bundle`.
+    UnsupportedCircuit {
+        num_blobs: usize,
+        num_evaluation_points: usize,
+    },
+    /// The binary form does not start with the `POEB` magic.
+    BadMagic,
+    UnsupportedVersion(u32),
+    /// The binary form ends before the named field is complete.
+    Truncated(&'static str),
+    TrailingBytes,
+    /// The JSON form is malformed or misses a field.
+    InvalidJson(String),
+    /// The named field is malformed, e.g. a JSON field that is not a hex string of the right
+    /// length, or a versioned hash that does not belong to the commitment.
+    InvalidField(&'static str),
+    /// The bundle was produced for a circuit with a different digest.
+    CircuitMismatch,
+    /// The proof does not deserialize for the circuit, or disagrees with the rest of the bundle.
+    InvalidProof(&'static str),
+    /// Reading or writing the bundle file failed.
+    Io(String),
+}
+
+impl fmt::Display for ProofBundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedCircuit {
+                num_blobs,
+                num_evaluation_points,
+            } => write!(
+                f,
+                "proof bundles need a single-blob, single-point circuit, got \
+                 num_blobs = {num_blobs}, num_evaluation_points = {num_evaluation_points}"
+            ),
+            Self::BadMagic => write!(f, "not a proof bundle"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported proof bundle version {version}")
+            }
+            Self::Truncated(field) => write!(f, "truncated proof bundle at {field}"),
+            Self::TrailingBytes => write!(f, "trailing bytes after proof bundle"),
+            Self::InvalidJson(error) => write!(f, "invalid proof bundle JSON: {error}"),
+            Self::InvalidField(field) => write!(f, "invalid proof bundle field {field}"),
+            Self::CircuitMismatch => write!(f, "proof bundle is for a different circuit"),
+            Self::InvalidProof(reason) => write!(f, "invalid proof in bundle: {reason}"),
+            Self::Io(error) => write!(f, "proof bundle I/O failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ProofBundleError {}
+
+impl ProofBundle {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&ProofBundleJson {
+            version: PROOF_BUNDLE_VERSION,
+            versioned_hash: to_hex(&self.versioned_hash),
+            kzg_commitment: to_hex(&self.kzg_commitment),
+            x: to_hex(&self.x),
+            y: to_hex(&self.y),
+            kzg_proof: to_hex(&self.kzg_proof),
+            proof: to_hex(&self.proof),
+            circuit_digest: to_hex(&self.circuit_digest),
+        })
+        .unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ProofBundleError> {
+        let bundle: ProofBundleJson = serde_json::from_str(json)
+            .map_err(|error| ProofBundleError::InvalidJson(error.to_string()))?;
+        if bundle.version != PROOF_BUNDLE_VERSION {
+            return Err(ProofBundleError::UnsupportedVersion(bundle.version));
+        }
+
+        Ok(Self {
+            versioned_hash: from_hex_array("versioned_hash", &bundle.versioned_hash)?,
+            kzg_commitment: from_hex_array("kzg_commitment", &bundle.kzg_commitment)?,
+            x: from_hex_array("x", &bundle.x)?,
+            y: from_hex_array("y", &bundle.y)?,
+            kzg_proof: from_hex_array("kzg_proof", &bundle.kzg_proof)?,
+            proof: from_hex("proof", &bundle.proof)?,
+            circuit_digest: from_hex_array("circuit_digest", &bundle.circuit_digest)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [
+            PROOF_BUNDLE_MAGIC.as_slice(),
+            &PROOF_BUNDLE_VERSION.to_le_bytes(),
+            &self.versioned_hash,
+            &self.kzg_commitment,
+            &self.x,
+            &self.y,
+            &self.kzg_proof,
+            &self.circuit_digest,
+            &u32::try_from(self.proof.len()).unwrap().to_le_bytes(),
+            &self.proof,
+        ]
+        .concat()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofBundleError> {
+        let mut reader = bytes;
+        let mut take = |field: &'static str, len: usize| {
+            let remaining: &[u8] = reader;
+            if remaining.len() < len {
+                return Err(ProofBundleError::Truncated(field));
+            }
+            let (head, tail) = remaining.split_at(len);
+            reader = tail;
+            Ok(head)
+        };
+
+        if take("magic", 4)? != PROOF_BUNDLE_MAGIC {
+            return Err(ProofBundleError::BadMagic);
+        }
+        let version = u32::from_le_bytes(take("version", 4)?.try_into().unwrap());
+        if version != PROOF_BUNDLE_VERSION {
+            return Err(ProofBundleError::UnsupportedVersion(version));
+        }
+
+        let versioned_hash = take("versioned_hash", 32)?.try_into().unwrap();
+        let kzg_commitment = take("kzg_commitment", BYTES_PER_G1)?.try_into().unwrap();
+        let x = take("x", BYTES_PER_FIELD_ELEMENT)?.try_into().unwrap();
+        let y = take("y", BYTES_PER_FIELD_ELEMENT)?.try_into().unwrap();
+        let kzg_proof = take("kzg_proof", BYTES_PER_G1)?.try_into().unwrap();
+        let circuit_digest = take("circuit_digest", 32)?.try_into().unwrap();
+        let proof_len = u32::from_le_bytes(take("proof length", 4)?.try_into().unwrap()) as usize;
+        let proof = take("proof", proof_len)?.to_vec();
+        if !reader.is_empty() {
+            return Err(ProofBundleError::TrailingBytes);
+        }
+
+        Ok(Self {
+            versioned_hash,
+            kzg_commitment,
+            x,
+            y,
+            kzg_proof,
+            proof,
+            circuit_digest,
+        })
+    }
+}
+
+pub fn write_proof_bundle_json(path: &Path, bundle: &ProofBundle) -> Result<(), ProofBundleError> {
+    fs::write(path, bundle.to_json()).map_err(io_error)
+}
+
+pub fn read_proof_bundle_json(path: &Path) -> Result<ProofBundle, ProofBundleError> {
+    ProofBundle::from_json(&fs::read_to_string(path).map_err(io_error)?)
+}
+
+pub fn write_proof_bundle(path: &Path, bundle: &ProofBundle) -> Result<(), ProofBundleError> {
+    fs::write(path, bundle.to_bytes()).map_err(io_error)
+}
+
+pub fn read_proof_bundle(path: &Path) -> Result<ProofBundle, ProofBundleError> {
+    ProofBundle::from_bytes(&fs::read(path).map_err(io_error)?)
+}
+
+fn io_error(error: std::io::Error) -> ProofBundleError {
+    ProofBundleError::Io(error.to_string())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex(field: &'static str, hex_string: &str) -> Result<Vec<u8>, ProofBundleError> {
+    let hex_digits = hex_string.strip_prefix("0x").unwrap_or(hex_string);
+    hex::decode(hex_digits).map_err(|_| ProofBundleError::InvalidField(field))
+}
+
+fn from_hex_array<const N: usize>(
+    field: &'static str,
+    hex_string: &str,
+) -> Result<[u8; N], ProofBundleError> {
+    from_hex(field, hex_string)?
+        .try_into()
+        .map_err(|_| ProofBundleError::InvalidField(field))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{
+        read_proof_bundle, read_proof_bundle_json, write_proof_bundle, write_proof_bundle_json,
+        ProofBundle, ProofBundleError, PROOF_BUNDLE_VERSION,
+    };
+
+    fn bundle(proof_len: usize) -> ProofBundle {
+        ProofBundle {
+            versioned_hash: [0x01; 32],
+            kzg_commitment: [0x02; 48],
+            x: [0x03; 32],
+            y: [0x04; 32],
+            kzg_proof: [0x05; 48],
+            proof: (0..proof_len).map(|i| i as u8).collect(),
+            circuit_digest: [0x06; 32],
+        }
+    }
+
+    /// The JSON form of [`bundle`] with ``field`` replaced by ``value``.
+    fn json_with(field: &str, value: serde_json::Value) -> String {
+        let mut json: serde_json::Value = serde_json::from_str(&bundle(16).to_json()).unwrap();
+        json[field] = value;
+        json.to_string()
+    }
+
+    #[test]
+    fn json_round_trip() {
+        for proof_len in [0, 1, 1000] {
+            let bundle = bundle(proof_len);
+            assert_eq!(ProofBundle::from_json(&bundle.to_json()), Ok(bundle));
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        for proof_len in [0, 1, 1000] {
+            let bundle = bundle(proof_len);
+            assert_eq!(ProofBundle::from_bytes(&bundle.to_bytes()), Ok(bundle));
+        }
+    }
+
+    #[test]
+    fn truncated_bytes_are_rejected() {
+        let bytes = bundle(16).to_bytes();
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                ProofBundle::from_bytes(&bytes[..len]),
+                Err(ProofBundleError::Truncated(_))
+            ));
+        }
+
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert_eq!(
+            ProofBundle::from_bytes(&trailing),
+            Err(ProofBundleError::TrailingBytes)
+        );
+    }
+
+    #[test]
+    fn bad_magic_is_rejected() {
+        let mut bytes = bundle(16).to_bytes();
+        bytes[0] ^= 1;
+        assert_eq!(
+            ProofBundle::from_bytes(&bytes),
+            Err(ProofBundleError::BadMagic)
+        );
+    }
+
+    #[test]
+    fn bad_version_is_rejected() {
+        for version in [0, PROOF_BUNDLE_VERSION + 1] {
+            let mut bytes = bundle(16).to_bytes();
+            bytes[4..8].copy_from_slice(&version.to_le_bytes());
+            assert_eq!(
+                ProofBundle::from_bytes(&bytes),
+                Err(ProofBundleError::UnsupportedVersion(version))
+            );
+
+            assert_eq!(
+                ProofBundle::from_json(&json_with("version", version.into())),
+                Err(ProofBundleError::UnsupportedVersion(version))
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_json_is_rejected() {
+        let json = bundle(16).to_json();
+        assert!(matches!(
+            ProofBundle::from_json(&json[..json.len() / 2]),
+            Err(ProofBundleError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            ProofBundle::from_json(&json_with("x", serde_json::Value::Null)),
+            Err(ProofBundleError::InvalidJson(_))
+        ));
+
+        // one byte short, and not hex at all
+        for value in ["0x".to_string() + &"03".repeat(31), "0xzz".to_string()] {
+            assert_eq!(
+                ProofBundle::from_json(&json_with("x", value.into())),
+                Err(ProofBundleError::InvalidField("x"))
+            );
+        }
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let bundle = bundle(16);
+        let path = std::env::temp_dir().join(format!("poe-proof-bundle-{}", std::process::id()));
+
+        write_proof_bundle(&path, &bundle).unwrap();
+        assert_eq!(read_proof_bundle(&path), Ok(bundle.clone()));
+        write_proof_bundle_json(&path, &bundle).unwrap();
+        assert_eq!(read_proof_bundle_json(&path), Ok(bundle));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            read_proof_bundle(&path),
+            Err(ProofBundleError::Io(_))
+        ));
+        assert!(matches!(
+            read_proof_bundle_json(&path),
+            Err(ProofBundleError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,444 @@
+use std::path::Path;
+use std::{fmt, fs};
+
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use num::BigUint;
+use plonky2::field::types::Field;
+use sha2::{Digest, Sha256};
+
+use crate::blob_domain::{reverse_bits, BLOB_DOMAIN};
+use crate::blob_polynomial::{eval_at_native, BLOB_WIDTH, BLOB_WIDTH_BITS};
+use crate::bls12_381_scalar_field::BLS12381Scalar;
+use crate::file_utils::DIR_PATH;
+
+pub const TRUSTED_SETUP_FILE: &str = "trusted_setup.txt";
+pub const NUM_G2_POINTS: usize = 65;
+pub const BYTES_PER_G1: usize = 48;
+pub const BYTES_PER_G2: usize = 96;
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+pub const POINT_EVALUATION_INPUT_LENGTH: usize = 192;
+/// Number of u32 limbs of the compressed commitment when witnessed in the circuit.
+pub const KZG_COMMITMENT_LIMBS: usize = 12;
+
+/// The Ethereum KZG ceremony output, as published in the standard `trusted_setup.txt`.
+pub struct KzgSettings {
+    /// Lagrange-form G1 points, permuted into bit-reversed order to line up with blob elements.
+    pub g1_lagrange_brp: Vec<G1Affine>,
+    /// Monomial-form G2 points, `[s^i]_2`.
+    pub g2_monomial: Vec<G2Affine>,
+}
+
+/// Why the trusted setup could not be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrustedSetupError {
+    Io(String),
+    /// The named item is missing or is not a number / hex string of the right length.
+    Malformed(&'static str),
+    /// The setup is not the 4096-point one of EIP-4844.
+    UnexpectedPointCounts {
+        num_g1_points: usize,
+        num_g2_points: usize,
+    },
+    /// The named point is not a valid compressed point of its group.
+    InvalidPoint(&'static str),
+}
+
+impl fmt::Display for TrustedSetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "cannot read trusted setup: {error}"),
+            Self::Malformed(item) => write!(f, "malformed trusted setup {item}"),
+            Self::UnexpectedPointCounts {
+                num_g1_points,
+                num_g2_points,
+            } => write!(
+                f,
+                "expected {BLOB_WIDTH} G1 and {NUM_G2_POINTS} G2 points, got {num_g1_points} and \
+                 {num_g2_points}"
+            ),
+            Self::InvalidPoint(group) => write!(f, "invalid {group} point in trusted setup"),
+        }
+    }
+}
+
+impl std::error::Error for TrustedSetupError {}
+
+impl KzgSettings {
+    /// Loads `trusted_setup.txt` from the artifacts directory.
+    pub fn load() -> Result<Self, TrustedSetupError> {
+        Self::load_trusted_setup(&Path::new(DIR_PATH).join(TRUSTED_SETUP_FILE))
+    }
+
+    /// The file starts with the number of G1 and G2 points, followed by the hex encoded
+    /// compressed Lagrange-form G1 points in natural order and the monomial-form G2 points.
+    /// Anything after the G2 points (e.g. the monomial-form G1 points of newer setups) is ignored.
+    pub fn load_trusted_setup(path: &Path) -> Result<Self, TrustedSetupError> {
+        let contents =
+            fs::read_to_string(path).map_err(|error| TrustedSetupError::Io(error.to_string()))?;
+        let mut tokens = contents.split_whitespace();
+
+        let num_g1_points = parse_count(tokens.next(), "G1 point count")?;
+        let num_g2_points = parse_count(tokens.next(), "G2 point count")?;
+        if num_g1_points != BLOB_WIDTH || num_g2_points != NUM_G2_POINTS {
+            return Err(TrustedSetupError::UnexpectedPointCounts {
+                num_g1_points,
+                num_g2_points,
+            });
+        }
+
+        let g1_lagrange = (0..num_g1_points)
+            .map(|_| {
+                let bytes = parse_hex::<BYTES_PER_G1>(tokens.next(), "G1 point")?;
+                Option::<G1Affine>::from(G1Affine::from_compressed(&bytes))
+                    .ok_or(TrustedSetupError::InvalidPoint("G1"))
+            })
+            .collect::<Result<Vec<G1Affine>, _>>()?;
+        let g2_monomial = (0..num_g2_points)
+            .map(|_| {
+                let bytes = parse_hex::<BYTES_PER_G2>(tokens.next(), "G2 point")?;
+                Option::<G2Affine>::from(G2Affine::from_compressed(&bytes))
+                    .ok_or(TrustedSetupError::InvalidPoint("G2"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            g1_lagrange_brp: (0..num_g1_points)
+                .map(|i| g1_lagrange[reverse_bits(i, BLOB_WIDTH_BITS)])
+                .collect(),
+            g2_monomial,
+        })
+    }
+
+    /// Commits to a blob in evaluation form: `C = sum_(i=0)^WIDTH blob[i] * [L_i(s)]_1`.
+    pub fn blob_to_kzg_commitment(&self, blob: &[BLS12381Scalar; BLOB_WIDTH]) -> G1Affine {
+        self.g1_lincomb(blob)
+    }
+
+    /// Computes the KZG opening proof of the blob at ``z`` together with ``y = f(z)``.
+    ///
+    /// The proof commits to the quotient ``q(X) = (f(X) - y) / (X - z)`` in evaluation form. When
+    /// ``z`` is the i'th domain element, ``q(DOMAIN[i])`` is undefined by the formula above and is
+    /// computed as in the consensus specs' `compute_quotient_eval_within_domain`.
+    pub fn compute_kzg_proof(
+        &self,
+        blob: &[BLS12381Scalar; BLOB_WIDTH],
+        z: BLS12381Scalar,
+    ) -> (G1Affine, BLS12381Scalar) {
+        let y = eval_at_native(blob, z);
+        let roots_of_unity_brp = BLOB_DOMAIN.roots();
+
+        // avoid division by zero, the in-domain index is handled separately below
+        let denominators = roots_of_unity_brp
+            .iter()
+            .map(|root| {
+                let denominator = *root - z;
+                if denominator.is_zero() {
+                    BLS12381Scalar::ONE
+                } else {
+                    denominator
+                }
+            })
+            .collect::<Vec<_>>();
+        let denominators_inv = BLS12381Scalar::batch_multiplicative_inverse(&denominators);
+
+        let mut quotient: [BLS12381Scalar; BLOB_WIDTH] =
+            core::array::from_fn(|i| (blob[i] - y) * denominators_inv[i]);
+        if let Some(index) = BLOB_DOMAIN.index_of(&z) {
+            quotient[index] = compute_quotient_eval_within_domain(blob, z, y);
+        }
+
+        (self.g1_lincomb(&quotient), y)
+    }
+
+    /// Checks that ``proof`` opens ``commitment`` to ``y`` at ``z``, i.e. the pairing equation
+    ///    e(commitment - [y]_1, -[1]_2) * e(proof, [s]_2 - [z]_2) == 1
+    pub fn verify_kzg_proof(
+        &self,
+        commitment: &G1Affine,
+        z: BLS12381Scalar,
+        y: BLS12381Scalar,
+        proof: &G1Affine,
+    ) -> bool {
+        let x_minus_z = G2Affine::from(
+            G2Projective::from(self.g2_monomial[1]) - G2Affine::generator() * to_bls_scalar(&z),
+        );
+        let p_minus_y = G1Affine::from(
+            G1Projective::from(commitment) - G1Affine::generator() * to_bls_scalar(&y),
+        );
+
+        multi_miller_loop(&[
+            (&p_minus_y, &G2Prepared::from(-G2Affine::generator())),
+            (proof, &G2Prepared::from(x_minus_z)),
+        ])
+        .final_exponentiation()
+            == Gt::identity()
+    }
+
+    /// Runs the EIP-4844 point evaluation precompile on
+    /// `versioned_hash || z || y || commitment || proof`, returning
+    /// `FIELD_ELEMENTS_PER_BLOB || BLS_MODULUS` on success, both as 32-byte big-endian integers.
+    /// Returns `None` wherever the precompile would fail.
+    pub fn point_evaluation_precompile(
+        &self,
+        input: &[u8; POINT_EVALUATION_INPUT_LENGTH],
+    ) -> Option<[u8; 64]> {
+        let versioned_hash: [u8; 32] = input[0..32].try_into().unwrap();
+        let z = bytes_to_bls_field(input[32..64].try_into().unwrap())?;
+        let y = bytes_to_bls_field(input[64..96].try_into().unwrap())?;
+        let commitment_bytes: [u8; BYTES_PER_G1] = input[96..144].try_into().unwrap();
+        let proof_bytes: [u8; BYTES_PER_G1] = input[144..192].try_into().unwrap();
+
+        if kzg_to_versioned_hash(&commitment_bytes) != versioned_hash {
+            return None;
+        }
+
+        // `from_compressed` performs the subgroup check
+        let commitment = Option::<G1Affine>::from(G1Affine::from_compressed(&commitment_bytes))?;
+        let proof = Option::<G1Affine>::from(G1Affine::from_compressed(&proof_bytes))?;
+        if !self.verify_kzg_proof(&commitment, z, y, &proof) {
+            return None;
+        }
+
+        let mut output = [0u8; 64];
+        output[0..32].copy_from_slice(&biguint_to_bytes_be(&BigUint::from(BLOB_WIDTH)));
+        output[32..64].copy_from_slice(&biguint_to_bytes_be(&BLS12381Scalar::order()));
+        Some(output)
+    }
+
+    pub(crate) fn g1_lincomb(&self, scalars: &[BLS12381Scalar; BLOB_WIDTH]) -> G1Affine {
+        G1Affine::from(
+            self.g1_lagrange_brp
+                .iter()
+                .zip(scalars.iter())
+                .filter(|(_, scalar)| !scalar.is_zero())
+                .fold(G1Projective::identity(), |acc, (point, scalar)| {
+                    acc + point * to_bls_scalar(scalar)
+                }),
+        )
+    }
+}
+
+fn parse_count(token: Option<&str>, item: &'static str) -> Result<usize, TrustedSetupError> {
+    token
+        .and_then(|token| token.parse().ok())
+        .ok_or(TrustedSetupError::Malformed(item))
+}
+
+fn parse_hex<const N: usize>(
+    token: Option<&str>,
+    item: &'static str,
+) -> Result<[u8; N], TrustedSetupError> {
+    token
+        .and_then(|token| hex::decode(token).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(TrustedSetupError::Malformed(item))
+}
+
+/// q(z) for ``z`` in the domain:
+///    q(z) = sum_(DOMAIN[i] != z)  (f(DOMAIN[i]) - y) * DOMAIN[i] / (z * (z - DOMAIN[i]))
+fn compute_quotient_eval_within_domain(
+    blob: &[BLS12381Scalar; BLOB_WIDTH],
+    z: BLS12381Scalar,
+    y: BLS12381Scalar,
+) -> BLS12381Scalar {
+    blob.iter()
+        .zip(BLOB_DOMAIN.roots().iter())
+        .filter(|(_, root)| **root != z)
+        .map(|(coeff, root)| (*coeff - y) * *root / (z * (z - *root)))
+        .sum()
+}
+
+/// SHA-256 of the compressed commitment with its first byte replaced by the KZG version byte.
+pub fn kzg_to_versioned_hash(commitment: &[u8; BYTES_PER_G1]) -> [u8; 32] {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Parses a 32-byte big-endian field element, rejecting non-canonical encodings.
+pub fn bytes_to_bls_field(bytes: &[u8; BYTES_PER_FIELD_ELEMENT]) -> Option<BLS12381Scalar> {
+    let value = BigUint::from_bytes_be(bytes);
+    if value >= BLS12381Scalar::order() {
+        return None;
+    }
+    Some(BLS12381Scalar::from_noncanonical_biguint(value))
+}
+
+fn biguint_to_bytes_be(value: &BigUint) -> [u8; BYTES_PER_FIELD_ELEMENT] {
+    let bytes = value.to_bytes_be();
+    let mut result = [0u8; BYTES_PER_FIELD_ELEMENT];
+    result[BYTES_PER_FIELD_ELEMENT - bytes.len()..].copy_from_slice(&bytes);
+    result
+}
+
+pub fn to_bls_scalar(x: &BLS12381Scalar) -> Scalar {
+    Scalar::from_raw(x.to_raw())
+}
+
+/// The 48-byte compressed commitment as a big-endian integer, the form expected by
+/// `read_kzg_commitment_in_goldilocks`.
+pub fn kzg_commitment_to_biguint(commitment: &G1Affine) -> BigUint {
+    BigUint::from_bytes_be(&commitment.to_compressed())
+}
+
+/// Converts a blob read by `read_blob` into field elements. EIP-4844 rejects blobs whose
+/// elements are not canonical, so such elements are rejected here rather than reduced.
+pub fn blob_to_scalars(blob: &[BigUint; BLOB_WIDTH]) -> [BLS12381Scalar; BLOB_WIDTH] {
+    blob.clone().map(|coeff| {
+        assert!(
+            coeff < BLS12381Scalar::order(),
+            "non-canonical blob element"
+        );
+        BLS12381Scalar::from_noncanonical_biguint(coeff)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lazy_static::lazy_static;
+    use plonky2::field::types::{Field, PrimeField, Sample};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{
+        biguint_to_bytes_be, blob_to_scalars, kzg_to_versioned_hash, KzgSettings,
+        TrustedSetupError, BLOB_DOMAIN, BLOB_WIDTH, NUM_G2_POINTS, POINT_EVALUATION_INPUT_LENGTH,
+    };
+    use crate::blob_polynomial::eval_at_native;
+    use crate::bls12_381_scalar_field::BLS12381Scalar;
+    use crate::file_utils::{
+        read_blob, read_bls1_381_scalar, read_kzg_commitment, read_kzg_proof, DIR_PATH,
+    };
+
+    lazy_static! {
+        static ref KZG_SETTINGS: KzgSettings = KzgSettings::load().unwrap();
+    }
+
+    #[test]
+    fn blob_to_kzg_commitment_matches_fixture() {
+        let blob = blob_to_scalars(&read_blob());
+        assert_eq!(
+            KZG_SETTINGS.blob_to_kzg_commitment(&blob).to_compressed(),
+            read_kzg_commitment()
+        );
+    }
+
+    #[test]
+    fn load_reports_errors() {
+        assert!(matches!(
+            KzgSettings::load_trusted_setup(&Path::new(DIR_PATH).join("missing")),
+            Err(TrustedSetupError::Io(_))
+        ));
+
+        // the blob fixture is a single hex string, not a point count
+        assert_eq!(
+            KzgSettings::load_trusted_setup(&Path::new(DIR_PATH).join("blob")).err(),
+            Some(TrustedSetupError::Malformed("G1 point count"))
+        );
+
+        assert_eq!(KZG_SETTINGS.g1_lagrange_brp.len(), BLOB_WIDTH);
+        assert_eq!(KZG_SETTINGS.g2_monomial.len(), NUM_G2_POINTS);
+    }
+
+    #[test]
+    fn compute_kzg_proof_matches_fixture() {
+        let blob = blob_to_scalars(&read_blob());
+        let x = BLS12381Scalar::from_noncanonical_biguint(read_bls1_381_scalar("x"));
+        let y = BLS12381Scalar::from_noncanonical_biguint(read_bls1_381_scalar("y"));
+
+        let (proof, y_computed) = KZG_SETTINGS.compute_kzg_proof(&blob, x);
+        assert_eq!(y_computed, y);
+        assert_eq!(proof.to_compressed(), read_kzg_proof());
+    }
+
+    #[test]
+    fn kzg_proof_round_trip() {
+        let blob = blob_to_scalars(&read_blob());
+        let commitment = KZG_SETTINGS.blob_to_kzg_commitment(&blob);
+        let mut rng = StdRng::seed_from_u64(0x6b7a67);
+
+        // the last two points are in the domain, where the quotient is computed separately
+        for z in [
+            BLS12381Scalar::sample(&mut rng),
+            BLS12381Scalar::sample(&mut rng),
+            BLOB_DOMAIN.root(0),
+            BLOB_DOMAIN.root(1234),
+        ] {
+            let (proof, y) = KZG_SETTINGS.compute_kzg_proof(&blob, z);
+            assert_eq!(y, eval_at_native(&blob, z));
+            assert!(KZG_SETTINGS.verify_kzg_proof(&commitment, z, y, &proof));
+            assert!(!KZG_SETTINGS.verify_kzg_proof(
+                &commitment,
+                z,
+                y + BLS12381Scalar::ONE,
+                &proof
+            ));
+        }
+    }
+
+    /// `versioned_hash || z || y || commitment || proof` for an opening of files/blob at ``z``.
+    fn precompile_input(z: BLS12381Scalar) -> [u8; POINT_EVALUATION_INPUT_LENGTH] {
+        let blob = blob_to_scalars(&read_blob());
+        let commitment = KZG_SETTINGS.blob_to_kzg_commitment(&blob).to_compressed();
+        let (proof, y) = KZG_SETTINGS.compute_kzg_proof(&blob, z);
+
+        [
+            kzg_to_versioned_hash(&commitment).as_slice(),
+            &biguint_to_bytes_be(&z.to_canonical_biguint()),
+            &biguint_to_bytes_be(&y.to_canonical_biguint()),
+            &commitment,
+            &proof.to_compressed(),
+        ]
+        .concat()
+        .try_into()
+        .unwrap()
+    }
+
+    #[test]
+    fn point_evaluation_precompile_accepts_valid_openings() {
+        let output = KZG_SETTINGS
+            .point_evaluation_precompile(&precompile_input(BLS12381Scalar::from_canonical_u64(7)))
+            .unwrap();
+        assert_eq!(output[..32], biguint_to_bytes_be(&BLOB_WIDTH.into()));
+        assert_eq!(output[32..], biguint_to_bytes_be(&BLS12381Scalar::order()));
+    }
+
+    #[test]
+    fn point_evaluation_precompile_rejects_invalid_inputs() {
+        let input = precompile_input(BLS12381Scalar::from_canonical_u64(7));
+
+        let mut bad_versioned_hash = input;
+        bad_versioned_hash[0] = 0x02;
+        assert_eq!(
+            KZG_SETTINGS.point_evaluation_precompile(&bad_versioned_hash),
+            None
+        );
+
+        // z = P is the non-canonical encoding of zero
+        let mut non_canonical_z = precompile_input(BLS12381Scalar::ZERO);
+        non_canonical_z[32..64].copy_from_slice(&biguint_to_bytes_be(&BLS12381Scalar::order()));
+        assert_eq!(
+            KZG_SETTINGS.point_evaluation_precompile(&non_canonical_z),
+            None
+        );
+
+        // a valid proof, but for another point
+        let mut wrong_proof = input;
+        wrong_proof[144..]
+            .copy_from_slice(&precompile_input(BLS12381Scalar::from_canonical_u64(8))[144..]);
+        assert_eq!(KZG_SETTINGS.point_evaluation_precompile(&wrong_proof), None);
+
+        // a corrupted proof encoding
+        let mut tampered_proof = input;
+        tampered_proof[150] ^= 1;
+        assert_eq!(
+            KZG_SETTINGS.point_evaluation_precompile(&tampered_proof),
+            None
+        );
+    }
+}
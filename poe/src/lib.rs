@@ -1,5 +1,9 @@
 pub mod blob_domain;
 pub mod blob_polynomial;
 pub mod bls12_381_scalar_field;
+pub mod byte_hashes;
+pub mod fft;
 pub mod fiat_shamir;
 pub mod file_utils;
+pub mod kzg;
+pub mod poe_circuit;
@@ -0,0 +1,836 @@
+use circuit::bigint::biguint::{BigUintTarget, CircuitBuilderBiguint, WitnessBigUint};
+use circuit::poseidon2::hash::Poseidon2Hash;
+use circuit::types::config::{Builder, C, CIRCUIT_CONFIG, D, F};
+use num::BigUint;
+use plonky2::field::types::{Field, PrimeField, PrimeField64};
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, NUM_HASH_OUT_ELTS};
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+use plonky2::plonk::config::GenericHashOut;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+
+use crate::blob_polynomial::{MultiBlobPolynomial, BLOB_WIDTH};
+use crate::bls12_381_scalar_field::{BLS12381Scalar, BLS12_381_SCALAR_LIMBS};
+use crate::fiat_shamir::{fiat_shamir_multi_point_with_transcript, TranscriptTarget};
+use crate::file_utils::{ProofBundle, ProofBundleError};
+use crate::kzg::{
+    kzg_to_versioned_hash, BYTES_PER_FIELD_ELEMENT, BYTES_PER_G1, KZG_COMMITMENT_LIMBS,
+};
+
+/// Proof of equivalence between a KZG commitment to a blob and the circuit's own commitment to
+/// it: the blob is hashed in-circuit, the challenge point `x` is derived from both commitments,
+/// and the blob is evaluated at `x`. Checking the KZG opening of the commitment at `(x, y)`
+/// (e.g. with the point evaluation precompile) completes the argument.
+///
+/// [`Self::build_multi_blob`] covers all blobs of a transaction at once, with a single `x`
+/// derived from every commitment and one `y` per blob. [`Self::build_multi_point`] additionally
+/// evaluates them at several challenges. [`Self::build_with_public_commitment`] exposes the
+/// circuit commitment of a single blob, to link the proof to other circuits committing to it.
+///
+/// Public inputs are the KZG commitments, then the circuit commitment if it is public, then for
+/// every evaluation point `x` followed by the evaluations `y` of all blobs.
+///
+/// ``N`` is the blob width, [`BLOB_WIDTH`] for EIP-4844 blobs.
+pub struct PoeCircuit<const N: usize = BLOB_WIDTH> {
+    pub data: CircuitData<F, C, D>,
+    blobs: MultiBlobPolynomial<N>,
+    kzg_commitments: Vec<BigUintTarget>,
+    circuit_commitment: Option<HashOutTarget>,
+    num_evaluation_points: usize,
+}
+
+pub struct PoeProof(pub ProofWithPublicInputs<F, C, D>);
+
+impl PoeCircuit {
+    /// The configuration the prover binary uses.
+    pub fn default_config() -> CircuitConfig {
+        CircuitConfig {
+            num_wires: 136,
+            ..CIRCUIT_CONFIG
+        }
+    }
+}
+
+impl<const N: usize> PoeCircuit<N> {
+    pub fn build(config: CircuitConfig) -> Self {
+        Self::build_multi_blob(config, 1)
+    }
+
+    pub fn build_multi_blob(config: CircuitConfig, num_blobs: usize) -> Self {
+        Self::build_multi_point(config, num_blobs, 1)
+    }
+
+    /// Same as [`Self::build`], but the circuit commitment to the blob is a public input checked
+    /// against the hash of the blob, see
+    /// [`BlobPolynomial::commit_public`](crate::blob_polynomial::BlobPolynomial::commit_public).
+    /// It is decoded as [`PoePublicInputs::circuit_commitment`], and can be pinned to a value
+    /// committed to elsewhere with [`Self::prove_with_circuit_commitment`].
+    pub fn build_with_public_commitment(config: CircuitConfig) -> Self {
+        Self::build_circuit(config, 1, 1, true)
+    }
+
+    /// Evaluates the blobs at ``num_evaluation_points`` independent challenges, each of which
+    /// needs its own KZG opening. Further points beyond the first are squeezed from the
+    /// transcript, see `fiat_shamir_multi_point_with_transcript`.
+    pub fn build_multi_point(
+        config: CircuitConfig,
+        num_blobs: usize,
+        num_evaluation_points: usize,
+    ) -> Self {
+        Self::build_circuit(config, num_blobs, num_evaluation_points, false)
+    }
+
+    fn build_circuit(
+        config: CircuitConfig,
+        num_blobs: usize,
+        num_evaluation_points: usize,
+        public_circuit_commitment: bool,
+    ) -> Self {
+        let mut builder = Builder::new(config);
+
+        let kzg_commitments = (0..num_blobs)
+            .map(|_| {
+                let kzg_commitment = builder.add_virtual_biguint_target(KZG_COMMITMENT_LIMBS);
+                builder.register_public_input_biguint(&kzg_commitment);
+                kzg_commitment
+            })
+            .collect::<Vec<_>>();
+
+        let blobs = MultiBlobPolynomial::<N>::new(&mut builder, num_blobs);
+
+        let (circuit_commitments, circuit_commitment) = if public_circuit_commitment {
+            assert_eq!(
+                num_blobs, 1,
+                "only single-blob circuits expose the circuit commitment"
+            );
+            let circuit_commitment = blobs
+                .iter()
+                .next()
+                .unwrap()
+                .commit_public::<Poseidon2Hash>(&mut builder);
+            (vec![circuit_commitment], Some(circuit_commitment))
+        } else {
+            (blobs.commit::<Poseidon2Hash>(&mut builder), None)
+        };
+        let mut transcript = TranscriptTarget::new(&mut builder);
+        let evaluation_points = fiat_shamir_multi_point_with_transcript::<Poseidon2Hash, _>(
+            &mut builder,
+            &mut transcript,
+            &circuit_commitments,
+            &kzg_commitments,
+            num_evaluation_points,
+        );
+        let evaluation_results = blobs.eval_at_many(&mut builder, &evaluation_points);
+        for (evaluation_point, evaluation_results) in
+            evaluation_points.iter().zip(evaluation_results.iter())
+        {
+            builder.register_public_input_biguint(&evaluation_point.value);
+            evaluation_results.iter().for_each(|evaluation_result| {
+                builder.register_public_input_biguint(&evaluation_result.value)
+            });
+        }
+
+        Self {
+            data: builder.build::<C>(),
+            blobs,
+            kzg_commitments,
+            circuit_commitment,
+            num_evaluation_points,
+        }
+    }
+
+    pub fn num_blobs(&self) -> usize {
+        self.blobs.len()
+    }
+
+    pub fn num_evaluation_points(&self) -> usize {
+        self.num_evaluation_points
+    }
+
+    pub fn has_public_commitment(&self) -> bool {
+        self.circuit_commitment.is_some()
+    }
+
+    /// Proves the equivalence for ``blob`` and its compressed KZG commitment. Panics if the
+    /// witness does not satisfy the circuit.
+    pub fn prove(
+        &self,
+        blob: &[BLS12381Scalar; N],
+        kzg_commitment: &[u8; BYTES_PER_G1],
+    ) -> PoeProof {
+        self.prove_multi_blob(
+            std::slice::from_ref(blob),
+            std::slice::from_ref(kzg_commitment),
+        )
+    }
+
+    /// Same as [`Self::prove`] for a circuit built with [`Self::build_with_public_commitment`],
+    /// additionally setting the public circuit commitment to ``circuit_commitment``, e.g. the value
+    /// an upstream circuit committed to. Panics if it is not the commitment of ``blob``.
+    pub fn prove_with_circuit_commitment(
+        &self,
+        blob: &[BLS12381Scalar; N],
+        kzg_commitment: &[u8; BYTES_PER_G1],
+        circuit_commitment: HashOut<F>,
+    ) -> PoeProof {
+        let circuit_commitment_target = self
+            .circuit_commitment
+            .expect("circuit was built without a public circuit commitment");
+
+        let mut pw = self.witness(
+            std::slice::from_ref(blob),
+            std::slice::from_ref(kzg_commitment),
+        );
+        pw.set_hash_target(circuit_commitment_target, circuit_commitment);
+
+        PoeProof(self.data.prove(pw).unwrap())
+    }
+
+    /// Multi-blob counterpart of [`Self::prove`], taking the blobs and their commitments in
+    /// transaction order.
+    pub fn prove_multi_blob(
+        &self,
+        blobs: &[[BLS12381Scalar; N]],
+        kzg_commitments: &[[u8; BYTES_PER_G1]],
+    ) -> PoeProof {
+        let pw = self.witness(blobs, kzg_commitments);
+        PoeProof(self.data.prove(pw).unwrap())
+    }
+
+    fn witness(
+        &self,
+        blobs: &[[BLS12381Scalar; N]],
+        kzg_commitments: &[[u8; BYTES_PER_G1]],
+    ) -> PartialWitness<F> {
+        assert_eq!(blobs.len(), self.num_blobs());
+        assert_eq!(kzg_commitments.len(), self.num_blobs());
+
+        let mut pw = PartialWitness::new();
+        for (blob, blob_target) in blobs.iter().zip(self.blobs.iter()) {
+            blob.iter()
+                .zip(blob_target.iter())
+                .for_each(|(coeff, coeff_target)| {
+                    pw.set_biguint_target(&coeff_target.value, &coeff.to_canonical_biguint());
+                });
+        }
+        for (kzg_commitment, kzg_commitment_target) in
+            kzg_commitments.iter().zip(self.kzg_commitments.iter())
+        {
+            pw.set_biguint_target(
+                kzg_commitment_target,
+                &BigUint::from_bytes_be(kzg_commitment),
+            );
+        }
+
+        pw
+    }
+
+    pub fn verify(&self, proof: &PoeProof) -> bool {
+        self.data.verify(proof.0.clone()).is_ok()
+    }
+
+    pub fn circuit_digest(&self) -> [u8; 32] {
+        self.data
+            .verifier_only
+            .circuit_digest
+            .to_bytes()
+            .try_into()
+            .unwrap()
+    }
+
+    /// Bundles a proof of this circuit with the KZG opening proof at its `(x, y)`. The bundle
+    /// format holds a single commitment and evaluation, so circuits built for several blobs or
+    /// evaluation points are rejected.
+    pub fn bundle(
+        &self,
+        proof: &PoeProof,
+        kzg_proof: &[u8; BYTES_PER_G1],
+    ) -> Result<ProofBundle, ProofBundleError> {
+        self.check_bundle_support()?;
+
+        let public_inputs = proof.public_inputs();
+        Ok(ProofBundle {
+            versioned_hash: kzg_to_versioned_hash(&public_inputs.kzg_commitment),
+            kzg_commitment: public_inputs.kzg_commitment,
+            x: public_inputs.x,
+            y: public_inputs.y,
+            kzg_proof: *kzg_proof,
+            proof: proof.0.to_bytes(),
+            circuit_digest: self.circuit_digest(),
+        })
+    }
+
+    /// Recovers the proof from a bundle, checking that it was produced for this circuit and that
+    /// its public inputs match the rest of the bundle. The proof itself is not verified, see
+    /// [`Self::verify`].
+    pub fn proof_from_bundle(&self, bundle: &ProofBundle) -> Result<PoeProof, ProofBundleError> {
+        self.check_bundle_support()?;
+        if bundle.circuit_digest != self.circuit_digest() {
+            return Err(ProofBundleError::CircuitMismatch);
+        }
+        if bundle.versioned_hash != kzg_to_versioned_hash(&bundle.kzg_commitment) {
+            return Err(ProofBundleError::InvalidField("versioned_hash"));
+        }
+
+        let proof = ProofWithPublicInputs::from_bytes(bundle.proof.clone(), &self.data.common)
+            .map_err(|_| ProofBundleError::InvalidProof("proof does not deserialize"))?;
+        if proof.public_inputs.len() != self.data.common.num_public_inputs {
+            return Err(ProofBundleError::InvalidProof(
+                "wrong number of public inputs",
+            ));
+        }
+
+        // Compare in the limb encoding, decoding untrusted limbs could fail.
+        let (kzg_commitment, rest) = proof.public_inputs.split_at(KZG_COMMITMENT_LIMBS);
+        let (x, y) =
+            rest[rest.len() - 2 * BLS12_381_SCALAR_LIMBS..].split_at(BLS12_381_SCALAR_LIMBS);
+        if kzg_commitment != bytes_be_to_limbs(&bundle.kzg_commitment) {
+            return Err(ProofBundleError::InvalidProof(
+                "KZG commitment differs from the bundle",
+            ));
+        }
+        if x != bytes_be_to_limbs(&bundle.x) {
+            return Err(ProofBundleError::InvalidProof("x differs from the bundle"));
+        }
+        if y != bytes_be_to_limbs(&bundle.y) {
+            return Err(ProofBundleError::InvalidProof("y differs from the bundle"));
+        }
+
+        Ok(PoeProof(proof))
+    }
+
+    fn check_bundle_support(&self) -> Result<(), ProofBundleError> {
+        if self.num_blobs() != 1 || self.num_evaluation_points() != 1 {
+            return Err(ProofBundleError::UnsupportedCircuit {
+                num_blobs: self.num_blobs(),
+                num_evaluation_points: self.num_evaluation_points(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl PoeProof {
+    /// Public inputs of a single-blob proof.
+    pub fn public_inputs(&self) -> PoePublicInputs {
+        PoePublicInputs::from_public_inputs(&self.0.public_inputs)
+    }
+
+    pub fn multi_blob_public_inputs(&self) -> MultiBlobPublicInputs {
+        MultiBlobPublicInputs::from_public_inputs(&self.0.public_inputs)
+    }
+
+    pub fn multi_point_public_inputs(&self, num_blobs: usize) -> MultiPointPublicInputs {
+        MultiPointPublicInputs::from_public_inputs(&self.0.public_inputs, num_blobs)
+    }
+}
+
+/// Public inputs of a [`PoeCircuit`] proof in their usual byte encodings.
+///
+/// In the proof they are a flat list of u32 limbs, least significant first, as laid out by
+/// `register_public_input_biguint`: 12 limbs of the commitment, then 8 limbs each of `x` and
+/// `y`. A circuit built with [`PoeCircuit::build_with_public_commitment`] has the 4 elements of
+/// the circuit commitment between the KZG commitment and `x`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoePublicInputs {
+    /// The compressed KZG commitment.
+    pub kzg_commitment: [u8; BYTES_PER_G1],
+    /// The circuit commitment to the blob, if the circuit exposes it.
+    pub circuit_commitment: Option<HashOut<F>>,
+    /// The challenge point, big-endian.
+    pub x: [u8; BYTES_PER_FIELD_ELEMENT],
+    /// The blob evaluation ``y = f(x)``, big-endian.
+    pub y: [u8; BYTES_PER_FIELD_ELEMENT],
+}
+
+impl PoePublicInputs {
+    pub const NUM_ELEMENTS: usize = KZG_COMMITMENT_LIMBS + 2 * BLS12_381_SCALAR_LIMBS;
+    pub const NUM_ELEMENTS_WITH_CIRCUIT_COMMITMENT: usize = Self::NUM_ELEMENTS + NUM_HASH_OUT_ELTS;
+
+    pub fn from_public_inputs(public_inputs: &[F]) -> Self {
+        let has_circuit_commitment = match public_inputs.len() {
+            Self::NUM_ELEMENTS => false,
+            Self::NUM_ELEMENTS_WITH_CIRCUIT_COMMITMENT => true,
+            len => panic!("unexpected number of public inputs: {len}"),
+        };
+
+        let (kzg_commitment, rest) = public_inputs.split_at(KZG_COMMITMENT_LIMBS);
+        let (circuit_commitment, evaluation) = if has_circuit_commitment {
+            let (circuit_commitment, evaluation) = rest.split_at(NUM_HASH_OUT_ELTS);
+            let elements = circuit_commitment.try_into().unwrap();
+            (Some(HashOut { elements }), evaluation)
+        } else {
+            (None, rest)
+        };
+        let (x, y) = evaluation.split_at(BLS12_381_SCALAR_LIMBS);
+
+        Self {
+            kzg_commitment: limbs_to_bytes_be(kzg_commitment),
+            circuit_commitment,
+            x: limbs_to_bytes_be(x),
+            y: limbs_to_bytes_be(y),
+        }
+    }
+
+    /// Inverse of [`Self::from_public_inputs`].
+    pub fn to_public_inputs(&self) -> Vec<F> {
+        [
+            bytes_be_to_limbs(&self.kzg_commitment),
+            self.circuit_commitment
+                .map_or(Vec::new(), |commitment| commitment.elements.to_vec()),
+            bytes_be_to_limbs(&self.x),
+            bytes_be_to_limbs(&self.y),
+        ]
+        .concat()
+    }
+}
+
+/// Public inputs of a multi-blob [`PoeCircuit`] proof: 12 limbs per commitment, 8 limbs of `x`,
+/// then 8 limbs per evaluation. With a single blob this is the layout of [`PoePublicInputs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiBlobPublicInputs {
+    pub kzg_commitments: Vec<[u8; BYTES_PER_G1]>,
+    pub x: [u8; BYTES_PER_FIELD_ELEMENT],
+    /// The evaluation of every blob at `x`, in the order of the commitments.
+    pub ys: Vec<[u8; BYTES_PER_FIELD_ELEMENT]>,
+}
+
+impl MultiBlobPublicInputs {
+    pub fn num_elements(num_blobs: usize) -> usize {
+        num_blobs * (KZG_COMMITMENT_LIMBS + BLS12_381_SCALAR_LIMBS) + BLS12_381_SCALAR_LIMBS
+    }
+
+    pub fn from_public_inputs(public_inputs: &[F]) -> Self {
+        let num_blobs = public_inputs
+            .len()
+            .checked_sub(BLS12_381_SCALAR_LIMBS)
+            .expect("too few public inputs for a multi-blob proof")
+            / (KZG_COMMITMENT_LIMBS + BLS12_381_SCALAR_LIMBS);
+        assert_eq!(public_inputs.len(), Self::num_elements(num_blobs));
+
+        let (kzg_commitments, evaluations) =
+            public_inputs.split_at(num_blobs * KZG_COMMITMENT_LIMBS);
+        let (x, ys) = evaluations.split_at(BLS12_381_SCALAR_LIMBS);
+
+        Self {
+            kzg_commitments: kzg_commitments
+                .chunks(KZG_COMMITMENT_LIMBS)
+                .map(limbs_to_bytes_be)
+                .collect(),
+            x: limbs_to_bytes_be(x),
+            ys: ys
+                .chunks(BLS12_381_SCALAR_LIMBS)
+                .map(limbs_to_bytes_be)
+                .collect(),
+        }
+    }
+
+    /// Inverse of [`Self::from_public_inputs`].
+    pub fn to_public_inputs(&self) -> Vec<F> {
+        self.kzg_commitments
+            .iter()
+            .flat_map(|kzg_commitment| bytes_be_to_limbs(kzg_commitment))
+            .chain(bytes_be_to_limbs(&self.x))
+            .chain(self.ys.iter().flat_map(|y| bytes_be_to_limbs(y)))
+            .collect()
+    }
+}
+
+/// An evaluation point of a multi-point proof, with the evaluation of every blob at it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvaluationClaim {
+    pub x: [u8; BYTES_PER_FIELD_ELEMENT],
+    pub ys: Vec<[u8; BYTES_PER_FIELD_ELEMENT]>,
+}
+
+/// Public inputs of a [`PoeCircuit::build_multi_point`] proof: 12 limbs per commitment, then for
+/// every point 8 limbs of `x` and 8 limbs per evaluation. With a single point this is the layout
+/// of [`MultiBlobPublicInputs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiPointPublicInputs {
+    pub kzg_commitments: Vec<[u8; BYTES_PER_G1]>,
+    pub evaluations: Vec<EvaluationClaim>,
+}
+
+impl MultiPointPublicInputs {
+    pub fn num_elements(num_blobs: usize, num_evaluation_points: usize) -> usize {
+        num_blobs * KZG_COMMITMENT_LIMBS
+            + num_evaluation_points * (num_blobs + 1) * BLS12_381_SCALAR_LIMBS
+    }
+
+    /// The layout depends on the number of blobs, which can't be told from the length alone.
+    pub fn from_public_inputs(public_inputs: &[F], num_blobs: usize) -> Self {
+        let evaluation_limbs = (num_blobs + 1) * BLS12_381_SCALAR_LIMBS;
+        let num_evaluation_points = public_inputs
+            .len()
+            .checked_sub(num_blobs * KZG_COMMITMENT_LIMBS)
+            .expect("too few public inputs for a multi-point proof")
+            / evaluation_limbs;
+        assert_eq!(
+            public_inputs.len(),
+            Self::num_elements(num_blobs, num_evaluation_points)
+        );
+
+        let (kzg_commitments, evaluations) =
+            public_inputs.split_at(num_blobs * KZG_COMMITMENT_LIMBS);
+
+        Self {
+            kzg_commitments: kzg_commitments
+                .chunks(KZG_COMMITMENT_LIMBS)
+                .map(limbs_to_bytes_be)
+                .collect(),
+            evaluations: evaluations
+                .chunks(evaluation_limbs)
+                .map(|evaluation| {
+                    let (x, ys) = evaluation.split_at(BLS12_381_SCALAR_LIMBS);
+                    EvaluationClaim {
+                        x: limbs_to_bytes_be(x),
+                        ys: ys
+                            .chunks(BLS12_381_SCALAR_LIMBS)
+                            .map(limbs_to_bytes_be)
+                            .collect(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Inverse of [`Self::from_public_inputs`].
+    pub fn to_public_inputs(&self) -> Vec<F> {
+        self.kzg_commitments
+            .iter()
+            .flat_map(|kzg_commitment| bytes_be_to_limbs(kzg_commitment))
+            .chain(self.evaluations.iter().flat_map(|evaluation| {
+                bytes_be_to_limbs(&evaluation.x)
+                    .into_iter()
+                    .chain(evaluation.ys.iter().flat_map(|y| bytes_be_to_limbs(y)))
+            }))
+            .collect()
+    }
+}
+
+/// Big-endian bytes of an integer registered with `register_public_input_biguint`, i.e. given
+/// as u32 limbs, least significant first.
+fn limbs_to_bytes_be<const N: usize>(limbs: &[F]) -> [u8; N] {
+    assert_eq!(limbs.len() * 4, N);
+    let bytes = limbs
+        .iter()
+        .rev()
+        .flat_map(|limb| {
+            let limb = limb.to_canonical_u64();
+            assert!(limb <= u32::MAX as u64, "public input limb out of range");
+            (limb as u32).to_be_bytes()
+        })
+        .collect::<Vec<_>>();
+    bytes.try_into().unwrap()
+}
+
+fn bytes_be_to_limbs(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .rchunks(4)
+        .map(|limb| F::from_canonical_u32(u32::from_be_bytes(limb.try_into().unwrap())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use circuit::poseidon2::hash::Poseidon2Hash;
+    use circuit::types::config::F;
+    use lazy_static::lazy_static;
+    use num::BigUint;
+    use plonky2::field::types::{Field, Sample};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{
+        EvaluationClaim, MultiBlobPublicInputs, MultiPointPublicInputs, PoeCircuit, PoeProof,
+    };
+    use crate::blob_polynomial::{blob_commitment_native, eval_at_native, BLOB_WIDTH};
+    use crate::bls12_381_scalar_field::{BLS12381Scalar, BLS12_381_SCALAR_LIMBS};
+    use crate::fft::extend_evaluations;
+    use crate::fiat_shamir::{
+        challenge_point_multi_blob_native, challenge_point_native, challenge_points_native,
+    };
+    use crate::file_utils::{read_kzg_commitment, ProofBundle, ProofBundleError};
+    use crate::kzg::{bytes_to_bls_field, KzgSettings, BYTES_PER_G1, KZG_COMMITMENT_LIMBS};
+
+    /// Blob width of the test circuits, small enough to prove them in a test.
+    const WIDTH: usize = 16;
+
+    lazy_static! {
+        static ref PUBLIC_COMMITMENT_CIRCUIT: PoeCircuit<WIDTH> =
+            PoeCircuit::build_with_public_commitment(PoeCircuit::default_config());
+        static ref BLOB: [BLS12381Scalar; WIDTH] = random_blob(0);
+        /// The circuit absorbs the commitment as an opaque integer, so the fixture does not
+        /// need to commit to `BLOB`.
+        static ref KZG_COMMITMENT: [u8; BYTES_PER_G1] = read_kzg_commitment();
+        static ref BUNDLE: ProofBundle = consistent_bundle();
+    }
+
+    fn random_blob(seed: u64) -> [BLS12381Scalar; WIDTH] {
+        let mut rng = StdRng::seed_from_u64(seed);
+        std::array::from_fn(|_| BLS12381Scalar::sample(&mut rng))
+    }
+
+    /// A bundle of [`PUBLIC_COMMITMENT_CIRCUIT`] whose KZG opening proof is valid at its `(x, y)`.
+    ///
+    /// A ``WIDTH`` element blob is the start of its low-degree extension to a full blob, so both
+    /// evaluate to the same `y` anywhere and the full blob's KZG commitment and opening fit the
+    /// circuit's evaluation.
+    fn consistent_bundle() -> ProofBundle {
+        let kzg_settings = KzgSettings::load().unwrap();
+        let blob = random_blob(1);
+        let full_blob: [BLS12381Scalar; BLOB_WIDTH] = extend_evaluations(&blob, BLOB_WIDTH / WIDTH)
+            .try_into()
+            .unwrap();
+        let kzg_commitment = kzg_settings
+            .blob_to_kzg_commitment(&full_blob)
+            .to_compressed();
+
+        let circuit = &*PUBLIC_COMMITMENT_CIRCUIT;
+        let proof = circuit.prove(&blob, &kzg_commitment);
+        let public_inputs = proof.public_inputs();
+        let x = bytes_to_bls_field(&public_inputs.x).unwrap();
+        let (kzg_proof, y) = kzg_settings.compute_kzg_proof(&full_blob, x);
+        assert_eq!(bytes_to_bls_field(&public_inputs.y), Some(y));
+
+        let bundle = circuit.bundle(&proof, &kzg_proof.to_compressed()).unwrap();
+
+        // The bundle holds exactly what the point evaluation precompile takes.
+        let precompile_input = [
+            &bundle.versioned_hash[..],
+            &bundle.x,
+            &bundle.y,
+            &bundle.kzg_commitment,
+            &bundle.kzg_proof,
+        ]
+        .concat();
+        assert!(kzg_settings
+            .point_evaluation_precompile(&precompile_input.try_into().unwrap())
+            .is_some());
+
+        bundle
+    }
+
+    #[test]
+    fn public_commitment_is_the_blob_hash() {
+        let circuit = &*PUBLIC_COMMITMENT_CIRCUIT;
+        assert!(circuit.has_public_commitment());
+        let blob_commitment = blob_commitment_native::<Poseidon2Hash>(&*BLOB);
+
+        let proof = circuit.prove(&BLOB, &KZG_COMMITMENT);
+        assert!(circuit.verify(&proof));
+        let public_inputs = proof.public_inputs();
+        assert_eq!(public_inputs.kzg_commitment, *KZG_COMMITMENT);
+        assert_eq!(public_inputs.circuit_commitment, Some(blob_commitment));
+        assert_eq!(public_inputs.to_public_inputs(), proof.0.public_inputs);
+
+        // x and y are the challenge and evaluation a verifier recomputes from the blob.
+        let x = challenge_point_native::<Poseidon2Hash, _>(
+            &blob_commitment,
+            &BigUint::from_bytes_be(&*KZG_COMMITMENT),
+        );
+        assert_eq!(bytes_to_bls_field(&public_inputs.x), Some(x));
+        assert_eq!(
+            bytes_to_bls_field(&public_inputs.y),
+            Some(eval_at_native(&BLOB, x))
+        );
+
+        // Pinning the commitment to the blob hash proves the same statement.
+        let pinned_proof =
+            circuit.prove_with_circuit_commitment(&BLOB, &KZG_COMMITMENT, blob_commitment);
+        assert!(circuit.verify(&pinned_proof));
+        assert_eq!(pinned_proof.public_inputs(), public_inputs);
+
+        // A proof claiming any other commitment does not verify.
+        let mut tampered_proof = proof.0.clone();
+        tampered_proof.public_inputs[KZG_COMMITMENT_LIMBS] += F::ONE;
+        assert!(!circuit.verify(&PoeProof(tampered_proof)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn public_commitment_must_equal_the_blob_hash() {
+        let mut wrong_commitment = blob_commitment_native::<Poseidon2Hash>(&*BLOB);
+        wrong_commitment.elements[0] += F::ONE;
+
+        PUBLIC_COMMITMENT_CIRCUIT.prove_with_circuit_commitment(
+            &BLOB,
+            &KZG_COMMITMENT,
+            wrong_commitment,
+        );
+    }
+
+    #[test]
+    fn bundle_round_trip() {
+        let circuit = &*PUBLIC_COMMITMENT_CIRCUIT;
+        for bundle in [
+            ProofBundle::from_bytes(&BUNDLE.to_bytes()).unwrap(),
+            ProofBundle::from_json(&BUNDLE.to_json()).unwrap(),
+        ] {
+            assert_eq!(bundle, *BUNDLE);
+            let bundled_proof = circuit.proof_from_bundle(&bundle).unwrap();
+            assert!(circuit.verify(&bundled_proof));
+            assert_eq!(bundled_proof.public_inputs().x, bundle.x);
+            assert_eq!(bundled_proof.public_inputs().y, bundle.y);
+        }
+    }
+
+    #[test]
+    fn proof_from_bundle_rejects_mismatches() {
+        let circuit = &*PUBLIC_COMMITMENT_CIRCUIT;
+
+        let mut bundle = BUNDLE.clone();
+        bundle.circuit_digest[0] ^= 1;
+        assert_eq!(
+            circuit.proof_from_bundle(&bundle).err(),
+            Some(ProofBundleError::CircuitMismatch)
+        );
+
+        let mut bundle = BUNDLE.clone();
+        bundle.versioned_hash[31] ^= 1;
+        assert_eq!(
+            circuit.proof_from_bundle(&bundle).err(),
+            Some(ProofBundleError::InvalidField("versioned_hash"))
+        );
+
+        let mut bundle = BUNDLE.clone();
+        bundle.proof.truncate(bundle.proof.len() / 2);
+        assert!(matches!(
+            circuit.proof_from_bundle(&bundle),
+            Err(ProofBundleError::InvalidProof(_))
+        ));
+
+        let mut bundle = BUNDLE.clone();
+        bundle.x[31] ^= 1;
+        assert_eq!(
+            circuit.proof_from_bundle(&bundle).err(),
+            Some(ProofBundleError::InvalidProof("x differs from the bundle"))
+        );
+
+        let mut bundle = BUNDLE.clone();
+        bundle.y[31] ^= 1;
+        assert_eq!(
+            circuit.proof_from_bundle(&bundle).err(),
+            Some(ProofBundleError::InvalidProof("y differs from the bundle"))
+        );
+    }
+
+    #[test]
+    fn bundles_need_a_single_blob_and_point() {
+        let bundle = ProofBundle {
+            versioned_hash: [0; 32],
+            kzg_commitment: [0; BYTES_PER_G1],
+            x: [0; 32],
+            y: [0; 32],
+            kzg_proof: [0; BYTES_PER_G1],
+            proof: Vec::new(),
+            circuit_digest: [0; 32],
+        };
+
+        for (num_blobs, num_evaluation_points) in [(2, 1), (1, 2)] {
+            let circuit = PoeCircuit::<WIDTH>::build_multi_point(
+                PoeCircuit::default_config(),
+                num_blobs,
+                num_evaluation_points,
+            );
+            let error = ProofBundleError::UnsupportedCircuit {
+                num_blobs,
+                num_evaluation_points,
+            };
+            assert_eq!(circuit.check_bundle_support(), Err(error.clone()));
+            assert_eq!(circuit.proof_from_bundle(&bundle).err(), Some(error));
+        }
+    }
+
+    #[test]
+    fn multi_blob_outputs_match_native() {
+        let blobs = [random_blob(2), random_blob(3)];
+        let kzg_commitments = [*KZG_COMMITMENT, [0x80; BYTES_PER_G1]];
+
+        let circuit = PoeCircuit::<WIDTH>::build_multi_blob(PoeCircuit::default_config(), 2);
+        let proof = circuit.prove_multi_blob(&blobs, &kzg_commitments);
+        assert!(circuit.verify(&proof));
+        let public_inputs = proof.multi_blob_public_inputs();
+        assert_eq!(public_inputs.kzg_commitments, kzg_commitments);
+
+        let x = challenge_point_multi_blob_native::<Poseidon2Hash, _>(
+            &blobs.map(|blob| blob_commitment_native::<Poseidon2Hash>(&blob)),
+            &kzg_commitments.map(|kzg_commitment| BigUint::from_bytes_be(&kzg_commitment)),
+        );
+        assert_eq!(bytes_to_bls_field(&public_inputs.x), Some(x));
+        assert_eq!(public_inputs.ys.len(), blobs.len());
+        for (y, blob) in public_inputs.ys.iter().zip(blobs.iter()) {
+            assert_eq!(bytes_to_bls_field(y), Some(eval_at_native(blob, x)));
+        }
+    }
+
+    #[test]
+    fn multi_point_outputs_match_native() {
+        let blobs = [random_blob(4), random_blob(5)];
+        let kzg_commitments = [*KZG_COMMITMENT, [0x80; BYTES_PER_G1]];
+
+        let circuit = PoeCircuit::<WIDTH>::build_multi_point(PoeCircuit::default_config(), 2, 3);
+        let proof = circuit.prove_multi_blob(&blobs, &kzg_commitments);
+        assert!(circuit.verify(&proof));
+        let public_inputs = proof.multi_point_public_inputs(blobs.len());
+        assert_eq!(public_inputs.kzg_commitments, kzg_commitments);
+
+        let xs = challenge_points_native::<Poseidon2Hash, _>(
+            &blobs.map(|blob| blob_commitment_native::<Poseidon2Hash>(&blob)),
+            &kzg_commitments.map(|kzg_commitment| BigUint::from_bytes_be(&kzg_commitment)),
+            3,
+        );
+        assert_eq!(public_inputs.evaluations.len(), xs.len());
+        for (evaluation, x) in public_inputs.evaluations.iter().zip(xs) {
+            assert_eq!(bytes_to_bls_field(&evaluation.x), Some(x));
+            assert_eq!(evaluation.ys.len(), blobs.len());
+            for (y, blob) in evaluation.ys.iter().zip(blobs.iter()) {
+                assert_eq!(bytes_to_bls_field(y), Some(eval_at_native(blob, x)));
+            }
+        }
+    }
+
+    #[test]
+    fn multi_point_public_inputs_round_trip() {
+        let public_inputs = MultiPointPublicInputs {
+            kzg_commitments: vec![[0x01; BYTES_PER_G1], [0x02; BYTES_PER_G1]],
+            evaluations: (0..3u8)
+                .map(|i| EvaluationClaim {
+                    x: [0x10 + i; 32],
+                    ys: vec![[0x20 + i; 32], [0x30 + i; 32]],
+                })
+                .collect(),
+        };
+        let elements = public_inputs.to_public_inputs();
+        assert_eq!(elements.len(), MultiPointPublicInputs::num_elements(2, 3));
+        assert_eq!(
+            MultiPointPublicInputs::from_public_inputs(&elements, 2),
+            public_inputs
+        );
+
+        let public_inputs = MultiBlobPublicInputs {
+            kzg_commitments: public_inputs.kzg_commitments,
+            x: public_inputs.evaluations[0].x,
+            ys: public_inputs.evaluations[0].ys.clone(),
+        };
+        let elements = public_inputs.to_public_inputs();
+        assert_eq!(elements.len(), MultiBlobPublicInputs::num_elements(2));
+        assert_eq!(
+            MultiBlobPublicInputs::from_public_inputs(&elements),
+            public_inputs
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "too few public inputs")]
+    fn multi_blob_public_inputs_reject_short_input() {
+        MultiBlobPublicInputs::from_public_inputs(&[F::ZERO; BLS12_381_SCALAR_LIMBS - 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "too few public inputs")]
+    fn multi_point_public_inputs_reject_short_input() {
+        MultiPointPublicInputs::from_public_inputs(&[F::ZERO; KZG_COMMITMENT_LIMBS], 2);
+    }
+}